@@ -1,11 +1,27 @@
 mod animals_params {
-    #[derive(PartialEq, Debug, Clone)]
-    pub enum Species {
-        Herbivore,
-        Carnivore,
+    use crate::disease::Infection;
+    use crate::error::BioSimError;
+    use rand::Rng;
+    use rand_distr::{Distribution, Normal};
+    use serde::Deserialize;
+    use std::collections::{BTreeMap, HashMap};
+
+    // index of a species in the SpeciesRegistry, the built-in species always come first
+    #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy)]
+    pub struct SpeciesId(usize);
+
+    impl SpeciesId {
+        pub const HERBIVORE: SpeciesId = SpeciesId(0);
+        pub const CARNIVORE: SpeciesId = SpeciesId(1);
+        pub const OMNIVORE: SpeciesId = SpeciesId(2);
+        pub const APEX_PREDATOR: SpeciesId = SpeciesId(3);
+
+        pub fn index(self) -> usize {
+            self.0
+        }
     }
 
-    #[derive(PartialEq, Debug)]
+    #[derive(PartialEq, Debug, Clone, Copy)]
     pub struct Parameters {
         pub w_birth: f32,
         pub mu: f32,
@@ -60,13 +76,390 @@ mod animals_params {
         delta_phi_max: 10.0,
     };
 
+    pub const OMNIVORE: Parameters = Parameters {
+        w_birth: 7.0,
+        mu: 0.3,
+        sigma_birth: 1.2,
+        beta: 0.8,
+        eta: 0.08,
+        a_half: 40.0,
+        phi_age: 0.4,
+        w_half: 7.0,
+        phi_weight: 0.25,
+        gamma: 0.5,
+        zeta: 3.5,
+        xi: 1.15,
+        omega: 0.6,
+        f: 25.0,
+        delta_phi_max: 15.0,
+    };
+
+    pub const APEX_PREDATOR: Parameters = Parameters {
+        w_birth: 10.0,
+        mu: 0.3,
+        sigma_birth: 2.0,
+        beta: 0.7,
+        eta: 0.1,
+        a_half: 50.0,
+        phi_age: 0.25,
+        w_half: 8.0,
+        phi_weight: 0.3,
+        gamma: 0.3,
+        zeta: 3.5,
+        xi: 1.2,
+        omega: 0.7,
+        f: 60.0,
+        delta_phi_max: 8.0,
+    };
+
+    impl Parameters {
+        fn get_mut(&mut self, key: &str) -> Option<&mut f32> {
+            match key {
+                "w_birth" => Some(&mut self.w_birth),
+                "mu" => Some(&mut self.mu),
+                "sigma_birth" => Some(&mut self.sigma_birth),
+                "beta" => Some(&mut self.beta),
+                "eta" => Some(&mut self.eta),
+                "a_half" => Some(&mut self.a_half),
+                "phi_age" => Some(&mut self.phi_age),
+                "w_half" => Some(&mut self.w_half),
+                "phi_weight" => Some(&mut self.phi_weight),
+                "gamma" => Some(&mut self.gamma),
+                "zeta" => Some(&mut self.zeta),
+                "xi" => Some(&mut self.xi),
+                "omega" => Some(&mut self.omega),
+                "f" => Some(&mut self.f),
+                "delta_phi_max" => Some(&mut self.delta_phi_max),
+                _ => None,
+            }
+        }
+
+        // returns a copy with the given values replaced, or an error if any key or value is invalid
+        pub fn update(
+            &self,
+            partial_params: &HashMap<String, f32>,
+        ) -> Result<Parameters, BioSimError> {
+            let mut params = *self;
+
+            for (key, &value) in partial_params {
+                let invalid = |reason| BioSimError::InvalidParameter {
+                    key: key.clone(),
+                    value,
+                    reason,
+                };
+
+                if !value.is_finite() || value < 0.0 {
+                    return Err(invalid("must be a non-negative number"));
+                }
+                if key == "eta" && value > 1.0 {
+                    return Err(invalid("must be at most 1"));
+                }
+                if key == "delta_phi_max" && value <= 0.0 {
+                    return Err(invalid("must be strictly positive"));
+                }
+
+                let field = params
+                    .get_mut(key)
+                    .ok_or_else(|| BioSimError::UnknownParameter(key.clone()))?;
+                *field = value;
+            }
+
+            Ok(params)
+        }
+
+        pub fn get(&self, key: &str) -> Option<f32> {
+            let mut params = *self;
+            params.get_mut(key).map(|value| *value)
+        }
+
+        // a copy where every heritable parameter is shifted by gaussian noise,
+        // kept within the range update accepts
+        pub fn mutate(&self, genetics: &Genetics, rng: &mut impl Rng) -> Parameters {
+            let mut params = *self;
+
+            for (key, &sigma) in &genetics.mutation {
+                let Some(value) = params.get_mut(key) else {
+                    continue;
+                };
+
+                let mutated = *value + Normal::new(0.0, sigma).unwrap().sample(rng);
+                *value = match key.as_str() {
+                    "eta" => mutated.clamp(0.0, 1.0),
+                    "delta_phi_max" => mutated.max(f32::EPSILON),
+                    _ => mutated.max(0.0),
+                };
+            }
+
+            params
+        }
+
+        // a copy where every heritable parameter is taken from either self or mate, at random
+        pub fn recombine(
+            &self,
+            mate: &Parameters,
+            genetics: &Genetics,
+            rng: &mut impl Rng,
+        ) -> Parameters {
+            let mut params = *self;
+
+            for key in genetics.traits() {
+                if let (Some(value), Some(mate_value)) = (params.get_mut(key), mate.get(key)) {
+                    if rng.gen_bool(0.5) {
+                        *value = mate_value;
+                    }
+                }
+            }
+
+            params
+        }
+    }
+
+    // opt-in heredity: the listed parameters are stored per animal and passed on to
+    // newborns with gaussian noise of the given standard deviation, e.g. { mu = 0.02, f = 1.0 }
+    #[derive(PartialEq, Debug, Clone, Default, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct Genetics {
+        pub mutation: BTreeMap<String, f32>,
+    }
+
+    impl Genetics {
+        pub fn validate(&self) -> Result<(), BioSimError> {
+            for (key, &sigma) in &self.mutation {
+                HERBIVORE
+                    .get(key)
+                    .ok_or_else(|| BioSimError::UnknownParameter(key.clone()))?;
+
+                if !sigma.is_finite() || sigma < 0.0 {
+                    return Err(BioSimError::InvalidParameter {
+                        key: format!("mutation of {key}"),
+                        value: sigma,
+                        reason: "must be a non-negative number",
+                    });
+                }
+            }
+
+            Ok(())
+        }
+
+        // the heritable parameters, in a fixed order
+        pub fn traits(&self) -> impl Iterator<Item = &str> {
+            self.mutation.keys().map(String::as_str)
+        }
+    }
+
+    // what a species eats: the fodder of its cell and/or animals of the prey species
+    #[derive(PartialEq, Debug, Clone, Default)]
+    pub struct Diet {
+        pub fodder: bool,
+        pub prey: Vec<SpeciesId>,
+    }
+
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct SpeciesInfo {
+        // name used in scenario files and csv logs
+        pub name: String,
+        // name used in printed reports, e.g. "herbivores"
+        pub display_name: String,
+        // current parameters, used when creating animals
+        pub params: Parameters,
+        pub diet: Diet,
+    }
+
+    // a species defined in a scenario file, the parameters and diet start out as those of
+    // the based_on species. Prey must be species registered before this one.
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    pub struct SpeciesEntry {
+        pub name: String,
+        pub display_name: Option<String>,
+        pub based_on: String,
+        #[serde(default)]
+        pub params: HashMap<String, f32>,
+        pub fodder: Option<bool>,
+        pub prey: Option<Vec<String>>,
+    }
+
+    // every species that can live on the island, in the order they feed
+    #[derive(PartialEq, Debug, Clone)]
+    pub struct SpeciesRegistry {
+        species: Vec<SpeciesInfo>,
+    }
+
+    impl Default for SpeciesRegistry {
+        fn default() -> Self {
+            SpeciesRegistry {
+                species: vec![
+                    SpeciesInfo {
+                        name: "Herbivore".to_string(),
+                        display_name: "herbivores".to_string(),
+                        params: HERBIVORE,
+                        diet: Diet {
+                            fodder: true,
+                            prey: Vec::new(),
+                        },
+                    },
+                    SpeciesInfo {
+                        name: "Carnivore".to_string(),
+                        display_name: "carnivores".to_string(),
+                        params: CARNIVORE,
+                        diet: Diet {
+                            fodder: false,
+                            prey: vec![SpeciesId::HERBIVORE],
+                        },
+                    },
+                    SpeciesInfo {
+                        name: "Omnivore".to_string(),
+                        display_name: "omnivores".to_string(),
+                        params: OMNIVORE,
+                        diet: Diet {
+                            fodder: true,
+                            prey: vec![SpeciesId::HERBIVORE],
+                        },
+                    },
+                    SpeciesInfo {
+                        name: "ApexPredator".to_string(),
+                        display_name: "apex predators".to_string(),
+                        params: APEX_PREDATOR,
+                        diet: Diet {
+                            fodder: false,
+                            prey: vec![SpeciesId::CARNIVORE],
+                        },
+                    },
+                ],
+            }
+        }
+    }
+
+    impl SpeciesRegistry {
+        pub fn id(&self, name: &str) -> Result<SpeciesId, BioSimError> {
+            self.species
+                .iter()
+                .position(|info| info.name == name)
+                .map(SpeciesId)
+                .ok_or_else(|| BioSimError::UnknownSpecies(name.to_string()))
+        }
+
+        pub fn get(&self, species: SpeciesId) -> &SpeciesInfo {
+            &self.species[species.0]
+        }
+
+        pub fn params(&self, species: SpeciesId) -> &Parameters {
+            &self.get(species).params
+        }
+
+        pub fn set(
+            &mut self,
+            species: SpeciesId,
+            partial_params: &HashMap<String, f32>,
+        ) -> Result<Parameters, BioSimError> {
+            let params = self.params(species).update(partial_params)?;
+            self.species[species.0].params = params;
+
+            Ok(params)
+        }
+
+        pub fn iter(&self) -> impl Iterator<Item = (SpeciesId, &SpeciesInfo)> {
+            self.species
+                .iter()
+                .enumerate()
+                .map(|(i, info)| (SpeciesId(i), info))
+        }
+
+        pub fn ids(&self) -> impl Iterator<Item = SpeciesId> {
+            (0..self.species.len()).map(SpeciesId)
+        }
+
+        // species that only graze feed first, then those that hunt and graze, then those
+        // that only hunt. Within each group species feed in the order they were registered.
+        pub fn feeding_order(&self) -> Vec<SpeciesId> {
+            let mut order: Vec<SpeciesId> = self.ids().collect();
+
+            order.sort_by_key(|&species| {
+                let diet = &self.get(species).diet;
+                match (diet.fodder, diet.prey.is_empty()) {
+                    (true, true) => 0,
+                    (true, false) => 1,
+                    (false, _) => 2,
+                }
+            });
+
+            order
+        }
+
+        pub fn register(&mut self, info: SpeciesInfo) -> Result<SpeciesId, BioSimError> {
+            if self.id(&info.name).is_ok() {
+                return Err(BioSimError::DuplicateSpecies(info.name));
+            }
+            if let Some(prey) = info
+                .diet
+                .prey
+                .iter()
+                .find(|prey| prey.0 >= self.species.len())
+            {
+                return Err(BioSimError::UnknownSpecies(format!("{prey:?}")));
+            }
+
+            self.species.push(info);
+            Ok(SpeciesId(self.species.len() - 1))
+        }
+
+        pub fn register_entry(&mut self, entry: &SpeciesEntry) -> Result<SpeciesId, BioSimError> {
+            let base = self.get(self.id(&entry.based_on)?);
+            let params = base.params.update(&entry.params)?;
+
+            let prey = match &entry.prey {
+                Some(names) => names
+                    .iter()
+                    .map(|name| self.id(name))
+                    .collect::<Result<_, _>>()?,
+                None => base.diet.prey.clone(),
+            };
+            let diet = Diet {
+                fodder: entry.fodder.unwrap_or(base.diet.fodder),
+                prey,
+            };
+
+            self.register(SpeciesInfo {
+                name: entry.name.clone(),
+                display_name: entry
+                    .display_name
+                    .clone()
+                    .unwrap_or_else(|| entry.name.clone()),
+                params,
+                diet,
+            })
+        }
+    }
+
     #[derive(PartialEq, Debug, Clone)]
     pub struct Stats {
         pub age: u32,
         pub weight: f32,
         pub fitness: f32,
         pub alive: bool,
-        pub move_to: Option<((u32, u32))>,
+        pub move_to: Option<(u32, u32)>,
+        // only matters with sexual reproduction
+        pub sex: Sex,
+        // only changes for species with a disease
+        pub infection: Infection,
+    }
+
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize)]
+    pub enum Sex {
+        #[default]
+        Female,
+        Male,
+    }
+
+    // asexual: every animal can give birth, with the number of animals in the cell
+    // standing in for mates. sexual: only females give birth, each needs a male of
+    // the cell that has not fathered a newborn yet this year.
+    #[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum Reproduction {
+        #[default]
+        Asexual,
+        Sexual,
     }
 
     impl Stats {
@@ -77,6 +470,8 @@ mod animals_params {
                 fitness: 0.0,
                 alive: true,
                 move_to: None,
+                sex: Sex::Female,
+                infection: Infection::Susceptible,
             }
         }
 
@@ -87,21 +482,27 @@ mod animals_params {
                 fitness: 0.0,
                 alive: true,
                 move_to: None,
+                sex: Sex::Female,
+                infection: Infection::Susceptible,
             }
         }
     }
 }
 
-pub use animals_params::{Parameters, Species, Stats, CARNIVORE, HERBIVORE};
+use crate::disease::{DiseaseParams, Infection};
+pub use animals_params::{
+    Diet, Genetics, Parameters, Reproduction, Sex, SpeciesEntry, SpeciesId, SpeciesInfo,
+    SpeciesRegistry, Stats, APEX_PREDATOR, CARNIVORE, HERBIVORE, OMNIVORE,
+};
 use rand::Rng;
 use rand_distr::{Distribution, LogNormal};
 
-fn random() -> f32 {
-    rand::thread_rng().gen::<f32>()
+fn random(rng: &mut impl Rng) -> f32 {
+    rng.gen::<f32>()
 }
 
 pub trait AnimalTrait {
-    fn get_birthweight(&mut self, count_in_cell: usize) -> Option<f32> {
+    fn get_birthweight(&mut self, count_in_cell: usize, rng: &mut impl Rng) -> Option<f32> {
         let zeta = self.params().zeta;
         let w_birth = self.params().w_birth;
         let sigma_birth = self.params().sigma_birth;
@@ -120,7 +521,7 @@ pub trait AnimalTrait {
             gamma * self.stats_as_ref().fitness * count_in_cell as f32,
         );
 
-        if random() > probability_of_procreation {
+        if random(rng) > probability_of_procreation {
             return None;
         }
 
@@ -128,7 +529,7 @@ pub trait AnimalTrait {
         let sigma = f32::sqrt(f32::ln(1.0 + (sigma_birth.powi(2) / w_birth.powi(2))));
 
         let log_normal = LogNormal::new(mu, sigma).unwrap();
-        let newborn_weight = log_normal.sample(&mut rand::thread_rng());
+        let newborn_weight = log_normal.sample(rng);
 
         // check if parent has enought weight to give birth
         let parent_loss = xi * newborn_weight;
@@ -140,7 +541,7 @@ pub trait AnimalTrait {
         self.stats_as_mut().weight -= parent_loss;
         self.update_fitness();
 
-        return Some(newborn_weight);
+        Some(newborn_weight)
     }
 
     fn calc_fitness(&self) -> f32 {
@@ -158,7 +559,7 @@ pub trait AnimalTrait {
         let weight_parameter =
             1.0 / (1.0 + f32::exp(-phi_weight * (self.stats_as_ref().weight - w_half)));
 
-        return age_parameter * weight_parameter;
+        age_parameter * weight_parameter
     }
 
     fn update_fitness(&mut self) {
@@ -174,44 +575,73 @@ pub trait AnimalTrait {
         self.update_fitness();
     }
 
-    fn death(&mut self) {
+    // omega_factor scales omega, for climate events
+    fn death(&mut self, omega_factor: f32, rng: &mut impl Rng) {
         if self.stats_as_ref().weight <= 0.0 {
             self.stats_as_mut().alive = false;
         }
 
-        let probability_of_death = self.params().omega * (1.0 - self.stats_as_ref().fitness);
+        let omega = self.params().omega * omega_factor;
+        let probability_of_death = omega * (1.0 - self.stats_as_ref().fitness);
 
-        if random() < probability_of_death {
+        if random(rng) < probability_of_death {
             self.stats_as_mut().alive = false;
         }
     }
 
-    fn migrate(&self) -> bool {
-        let probability_of_migration = self.params().mu * self.stats_as_ref().fitness;
+    // extra weight loss and mortality of an infected animal, which may also recover
+    fn sickness(&mut self, disease: &DiseaseParams, rng: &mut impl Rng) {
+        if self.stats_as_ref().infection != Infection::Infected {
+            return;
+        }
 
-        if random() < probability_of_migration {
-            return true;
-        } else {
-            return false;
+        self.stats_as_mut().weight -= disease.weight_loss * self.stats_as_ref().weight;
+        self.update_fitness();
+
+        if random(rng) < disease.mortality {
+            self.stats_as_mut().alive = false;
+        } else if random(rng) < disease.recovery {
+            self.stats_as_mut().infection = Infection::Recovered;
+        }
+    }
+
+    fn infect(&mut self, probability: f32, rng: &mut impl Rng) {
+        if self.stats_as_ref().infection == Infection::Susceptible && random(rng) < probability {
+            self.stats_as_mut().infection = Infection::Infected;
         }
     }
 
-    fn species(&self) -> Species;
+    fn migrate(&self, rng: &mut impl Rng) -> bool {
+        let probability_of_migration = self.params().mu * self.stats_as_ref().fitness;
+
+        random(rng) < probability_of_migration
+    }
+
+    fn species(&self) -> SpeciesId;
 
     fn stats_as_mut(&mut self) -> &mut Stats;
 
     fn stats_as_ref(&self) -> &Stats;
 
     fn params(&self) -> &Parameters;
+
+    fn params_as_mut(&mut self) -> &mut Parameters;
+
+    fn set_params(&mut self, params: Parameters) {
+        *self.params_as_mut() = params;
+        self.update_fitness();
+    }
 }
 
+// an animal of any species, what it eats is decided by the diet of its species
 #[derive(PartialEq, Debug, Clone)]
-pub struct Herbivore {
-    pub species: Species,
+pub struct Animal {
+    pub species: SpeciesId,
     pub stats: Stats,
+    pub params: Parameters,
 }
 
-impl AnimalTrait for Herbivore {
+impl AnimalTrait for Animal {
     fn stats_as_mut(&mut self) -> &mut Stats {
         &mut self.stats
     }
@@ -221,152 +651,160 @@ impl AnimalTrait for Herbivore {
     }
 
     fn params(&self) -> &Parameters {
-        &HERBIVORE
+        &self.params
     }
 
-    fn species(&self) -> Species {
-        self.species.clone()
+    fn params_as_mut(&mut self) -> &mut Parameters {
+        &mut self.params
     }
-}
 
-impl Herbivore {
-    pub fn new() -> Herbivore {
-        let stats = Stats::new_default();
-        // update stats.fitness before init
-        let mut herb = Herbivore {
-            species: Species::Herbivore,
-            stats: Stats::new_default(),
-        };
-        herb.update_fitness();
-        herb
+    fn species(&self) -> SpeciesId {
+        self.species
     }
+}
 
-    pub fn from(stats: Stats) -> Herbivore {
-        let mut herb = Herbivore {
-            species: Species::Herbivore,
+impl Animal {
+    pub fn new(species: SpeciesId, stats: Stats, params: Parameters) -> Animal {
+        // update stats.fitness before use
+        let mut animal = Animal {
+            species,
             stats,
+            params,
         };
 
-        herb.update_fitness();
-        herb
+        animal.update_fitness();
+        animal
     }
 
-    pub fn procreation(&mut self, count_in_cell: usize) -> Option<Self> {
-        if let Some(newborn_weight) = self.get_birthweight(count_in_cell) {
-            let stats = Stats::from(0, newborn_weight);
-            return Some(Herbivore::from(stats));
-        }
-
-        None
+    // a herbivore with the default stats and parameters
+    pub fn herbivore() -> Animal {
+        Animal::new(SpeciesId::HERBIVORE, Stats::new_default(), HERBIVORE)
     }
 
-    pub fn feeding(&mut self, fodder: f32) -> f32 {
-        let amount_eaten;
-        if fodder < self.params().f {
-            amount_eaten = fodder;
-        } else {
-            amount_eaten = self.params().f;
-        }
+    // a carnivore with the default stats and parameters
+    pub fn carnivore() -> Animal {
+        Animal::new(SpeciesId::CARNIVORE, Stats::new_default(), CARNIVORE)
+    }
 
-        self.stats_as_mut().weight -= amount_eaten * self.params().beta;
-        self.update_fitness();
-        amount_eaten
+    // with genetics the newborn inherits mutated parameters, otherwise the parent's
+    pub fn procreation(
+        &mut self,
+        count_in_cell: usize,
+        genetics: Option<&Genetics>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        self.birth(count_in_cell, None, genetics, rng)
     }
-}
 
-#[derive(PartialEq, Debug, Clone)]
-pub struct Carnivore {
-    pub species: Species,
-    pub stats: Stats,
-}
+    // like procreation, but with genetics the newborn's parameters are recombined from
+    // both parents before they mutate. The newborn gets a random sex.
+    pub fn procreation_with_mate(
+        &mut self,
+        count_in_cell: usize,
+        mate: &Parameters,
+        genetics: Option<&Genetics>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        let mut newborn = self.birth(count_in_cell, Some(mate), genetics, rng)?;
+        newborn.stats.sex = if rng.gen_bool(0.5) {
+            Sex::Female
+        } else {
+            Sex::Male
+        };
 
-impl AnimalTrait for Carnivore {
-    fn stats_as_mut(&mut self) -> &mut Stats {
-        &mut self.stats
+        Some(newborn)
     }
 
-    fn stats_as_ref(&self) -> &Stats {
-        &self.stats
-    }
+    fn birth(
+        &mut self,
+        count_in_cell: usize,
+        mate: Option<&Parameters>,
+        genetics: Option<&Genetics>,
+        rng: &mut impl Rng,
+    ) -> Option<Self> {
+        let newborn_weight = self.get_birthweight(count_in_cell, rng)?;
+        let stats = Stats::from(0, newborn_weight);
+
+        let params = match (genetics, mate) {
+            (Some(genetics), Some(mate)) => self
+                .params
+                .recombine(mate, genetics, rng)
+                .mutate(genetics, rng),
+            (Some(genetics), None) => self.params.mutate(genetics, rng),
+            (None, _) => self.params,
+        };
 
-    fn params(&self) -> &Parameters {
-        &CARNIVORE
+        Some(Animal::new(self.species, stats, params))
     }
 
-    fn species(&self) -> Species {
-        self.species.clone()
+    // an omnivore with the default stats and parameters
+    pub fn omnivore() -> Animal {
+        Animal::new(SpeciesId::OMNIVORE, Stats::new_default(), OMNIVORE)
     }
-}
 
-impl Carnivore {
-    pub fn new() -> Carnivore {
-        let mut carn = Carnivore {
-            species: Species::Carnivore,
-            stats: Stats::new_default(),
-        };
-        carn.update_fitness();
-        carn
+    // an apex predator with the default stats and parameters
+    pub fn apex_predator() -> Animal {
+        Animal::new(
+            SpeciesId::APEX_PREDATOR,
+            Stats::new_default(),
+            APEX_PREDATOR,
+        )
     }
 
-    pub fn from(stats: Stats) -> Carnivore {
-        let mut carn = Carnivore {
-            species: Species::Carnivore,
-            stats,
-        };
-
-        carn.update_fitness();
-        carn
+    // eats from the fodder of the cell, returns the amount eaten
+    pub fn graze(&mut self, fodder: f32) -> f32 {
+        self.graze_up_to(fodder, self.params().f)
     }
 
-    pub fn procreation(&mut self, count_in_cell: usize) -> Option<Self> {
-        if let Some(newborn_weight) = self.get_birthweight(count_in_cell) {
-            let stats = Stats::from(0, newborn_weight);
-            return Some(Carnivore::from(stats));
-        }
+    // like graze, but eats at most appetite, for animals that have already eaten prey
+    pub fn graze_up_to(&mut self, fodder: f32, appetite: f32) -> f32 {
+        let amount_eaten = if fodder < appetite { fodder } else { appetite };
 
-        None
+        self.stats_as_mut().weight += amount_eaten * self.params().beta;
+        self.update_fitness();
+        amount_eaten
     }
 
-    pub fn feeding(&mut self, herb_sorted_lowest_fitness: &mut Vec<Herbivore>) {
+    // kills and eats prey until it has eaten f, returns the amount eaten
+    pub fn hunt(&mut self, prey_sorted_lowest_fitness: &mut [Animal], rng: &mut impl Rng) -> f32 {
         let delta_phi_max = self.params().delta_phi_max;
         let mut amount_eaten: f32 = 0.0;
 
-        for herbivore in herb_sorted_lowest_fitness {
+        for prey in prey_sorted_lowest_fitness {
             if amount_eaten >= self.params().f {
                 break;
             }
 
-            let diff_fitness = self.stats_as_ref().fitness - herbivore.stats.fitness;
+            let diff_fitness = self.stats_as_ref().fitness - prey.stats.fitness;
 
             if diff_fitness < 0.0 {
                 continue;
             }
 
-            let probability_of_killing;
-            if 0.0 < diff_fitness && diff_fitness < delta_phi_max {
-                probability_of_killing = (diff_fitness) / delta_phi_max;
+            let probability_of_killing = if 0.0 < diff_fitness && diff_fitness < delta_phi_max {
+                diff_fitness / delta_phi_max
             } else {
-                probability_of_killing = 1.0;
-            }
+                1.0
+            };
 
-            if random() >= probability_of_killing {
+            if random(rng) >= probability_of_killing {
                 continue;
             }
 
             let desired_food = self.params().f - amount_eaten;
-            let eating;
-
-            if herbivore.stats.weight > desired_food {
-                eating = desired_food;
+            let eating = if prey.stats.weight > desired_food {
+                desired_food
             } else {
-                eating = herbivore.stats.weight;
-            }
+                prey.stats.weight
+            };
 
             self.stats.weight += eating * self.params().beta;
-            herbivore.stats.alive = false;
+            prey.stats.alive = false;
             self.update_fitness();
             amount_eaten += eating;
         }
+
+        amount_eaten
     }
 }
 
@@ -376,11 +814,12 @@ mod test_creation {
 
     #[test]
     fn create_herb() {
-        let expected = Herbivore::new();
+        let expected = Animal::herbivore();
 
-        let mut result = Herbivore {
-            species: Species::Herbivore,
+        let mut result = Animal {
+            species: SpeciesId::HERBIVORE,
             stats: Stats::new_default(),
+            params: HERBIVORE,
         };
         result.update_fitness();
 
@@ -388,11 +827,12 @@ mod test_creation {
     }
     #[test]
     fn create_carn() {
-        let expected = Carnivore::new();
+        let expected = Animal::carnivore();
 
-        let mut result = Carnivore {
-            species: Species::Carnivore,
+        let mut result = Animal {
+            species: SpeciesId::CARNIVORE,
             stats: Stats::new_default(),
+            params: CARNIVORE,
         };
         result.update_fitness();
 
@@ -404,28 +844,29 @@ mod test_creation {
 mod test_attributes {
     // test the rest of the methods
     use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
 
     #[test]
     fn test_age() {
-        let mut herb = Herbivore::new();
+        let mut herb = Animal::herbivore();
         herb.aging();
         assert_eq!(herb.stats.age, 6);
 
-        let mut carn = Carnivore::new();
+        let mut carn = Animal::carnivore();
         carn.aging();
         assert_eq!(carn.stats.age, 6);
     }
 
     #[test]
     fn test_loss_of_weight() {
-        let mut herb = Herbivore::new();
+        let mut herb = Animal::herbivore();
         herb.stats.weight = 5.0;
         herb.loss_of_weight();
 
         // weight started as 5.0
         assert_eq!(herb.stats.weight, 4.75);
 
-        let mut carn = Carnivore::new();
+        let mut carn = Animal::carnivore();
         carn.stats.weight = 5.0;
         carn.loss_of_weight();
 
@@ -435,23 +876,406 @@ mod test_attributes {
 
     #[test]
     fn feeding_animal() {
-        let mut herb = Herbivore::new();
+        let mut herb = Animal::herbivore();
         herb.stats.weight = 10.0;
-        herb.feeding(1.0);
+        herb.graze(1.0);
 
-        assert_eq!(herb.stats.weight, 9.1);
+        assert_eq!(herb.stats.weight, 10.9);
 
         herb.stats.fitness = 0.01;
 
         let mut herbs = vec![herb];
 
-        let mut carn = Carnivore::new();
+        let mut carn = Animal::carnivore();
         carn.stats.weight = 20.0;
         carn.stats.fitness = 20.0;
-        carn.feeding(&mut herbs);
+        carn.hunt(&mut herbs, &mut StdRng::seed_from_u64(1));
 
         println!("{:#?}", herbs);
 
-        assert_eq!(carn.stats.weight, 26.825);
+        assert_eq!(carn.stats.weight, 28.175);
+    }
+}
+
+#[cfg(test)]
+mod test_randomness {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn same_seed_same_birthweights() {
+        let birthweights = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..50)
+                .filter_map(|_| {
+                    let mut herb =
+                        Animal::new(SpeciesId::HERBIVORE, Stats::from(5, 60.0), HERBIVORE);
+                    herb.procreation(20, None, &mut rng)
+                })
+                .map(|newborn| newborn.stats.weight)
+                .collect::<Vec<f32>>()
+        };
+
+        assert!(!birthweights(7).is_empty());
+        assert_eq!(birthweights(7), birthweights(7));
+        assert_ne!(birthweights(7), birthweights(8));
+    }
+}
+
+#[cfg(test)]
+mod test_parameters {
+    use super::*;
+    use crate::error::BioSimError;
+    use std::collections::HashMap;
+
+    fn partial(values: &[(&str, f32)]) -> HashMap<String, f32> {
+        values
+            .iter()
+            .map(|&(key, value)| (key.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn update_known_keys() {
+        let params = HERBIVORE
+            .update(&partial(&[("beta", 0.5), ("f", 20.0)]))
+            .unwrap();
+
+        assert_eq!(params.beta, 0.5);
+        assert_eq!(params.f, 20.0);
+        assert_eq!(params.eta, HERBIVORE.eta);
+    }
+
+    #[test]
+    fn reject_invalid_values() {
+        assert!(HERBIVORE.update(&partial(&[("omega", -0.1)])).is_err());
+        assert!(HERBIVORE.update(&partial(&[("eta", 1.5)])).is_err());
+        assert!(CARNIVORE
+            .update(&partial(&[("delta_phi_max", 0.0)]))
+            .is_err());
+        assert!(CARNIVORE.update(&partial(&[("f", f32::NAN)])).is_err());
+    }
+
+    #[test]
+    fn reject_unknown_key() {
+        let err = HERBIVORE.update(&partial(&[("speed", 1.0)])).unwrap_err();
+        assert!(matches!(err, BioSimError::UnknownParameter(key) if key == "speed"));
+    }
+
+    #[test]
+    fn species_table_keeps_old_values_on_error() {
+        let mut table = SpeciesRegistry::default();
+        let result = table.set(SpeciesId::CARNIVORE, &partial(&[("f", 10.0), ("eta", 2.0)]));
+
+        assert!(result.is_err());
+        assert_eq!(table.params(SpeciesId::CARNIVORE), &CARNIVORE);
+    }
+
+    #[test]
+    fn newborn_inherits_parameters() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let params = HERBIVORE.update(&partial(&[("gamma", 1.0)])).unwrap();
+        let mut herb = Animal::new(SpeciesId::HERBIVORE, Stats::from(5, 60.0), params);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let newborn = (0..100)
+            .find_map(|_| herb.procreation(50, None, &mut rng))
+            .unwrap();
+
+        assert_eq!(newborn.params, params);
+    }
+}
+
+#[cfg(test)]
+mod test_registry {
+    use super::*;
+    use crate::error::BioSimError;
+    use std::collections::HashMap;
+
+    fn fox() -> SpeciesEntry {
+        SpeciesEntry {
+            name: "Fox".to_string(),
+            display_name: Some("foxes".to_string()),
+            based_on: "Carnivore".to_string(),
+            params: HashMap::from([("f".to_string(), 20.0)]),
+            fodder: None,
+            prey: None,
+        }
+    }
+
+    #[test]
+    fn built_in_species() {
+        let registry = SpeciesRegistry::default();
+
+        assert_eq!(registry.id("Herbivore").unwrap(), SpeciesId::HERBIVORE);
+        assert_eq!(registry.id("Carnivore").unwrap(), SpeciesId::CARNIVORE);
+        assert!(registry.get(SpeciesId::HERBIVORE).diet.fodder);
+        assert_eq!(
+            registry.get(SpeciesId::CARNIVORE).diet.prey,
+            vec![SpeciesId::HERBIVORE]
+        );
+        assert!(matches!(
+            registry.id("Dragon"),
+            Err(BioSimError::UnknownSpecies(name)) if name == "Dragon"
+        ));
+    }
+
+    #[test]
+    fn register_species_from_entry() {
+        let mut registry = SpeciesRegistry::default();
+        let fox_id = registry.register_entry(&fox()).unwrap();
+
+        let info = registry.get(fox_id);
+        assert_eq!(registry.id("Fox").unwrap(), fox_id);
+        assert_eq!(info.display_name, "foxes");
+        assert_eq!(info.params.f, 20.0);
+        assert_eq!(info.params.beta, CARNIVORE.beta);
+        assert_eq!(info.diet.prey, vec![SpeciesId::HERBIVORE]);
+
+        let wolf = SpeciesEntry {
+            name: "Wolf".to_string(),
+            display_name: None,
+            prey: Some(vec!["Herbivore".to_string(), "Fox".to_string()]),
+            ..fox()
+        };
+        let wolf = registry.register_entry(&wolf).unwrap();
+        assert_eq!(
+            registry.get(wolf).diet.prey,
+            vec![SpeciesId::HERBIVORE, fox_id]
+        );
+        assert_eq!(registry.get(wolf).display_name, "Wolf");
+        assert_eq!(registry.ids().count(), 6);
+    }
+
+    #[test]
+    fn reject_invalid_entries() {
+        let mut registry = SpeciesRegistry::default();
+        registry.register_entry(&fox()).unwrap();
+
+        let err = registry.register_entry(&fox()).unwrap_err();
+        assert!(matches!(err, BioSimError::DuplicateSpecies(name) if name == "Fox"));
+
+        let unknown_prey = SpeciesEntry {
+            name: "Owl".to_string(),
+            prey: Some(vec!["Mouse".to_string()]),
+            ..fox()
+        };
+        let err = registry.register_entry(&unknown_prey).unwrap_err();
+        assert!(matches!(err, BioSimError::UnknownSpecies(name) if name == "Mouse"));
+    }
+
+    #[test]
+    fn feeding_order() {
+        let mut registry = SpeciesRegistry::default();
+        let fox = registry.register_entry(&fox()).unwrap();
+
+        assert_eq!(
+            registry.feeding_order(),
+            vec![
+                SpeciesId::HERBIVORE,
+                SpeciesId::OMNIVORE,
+                SpeciesId::CARNIVORE,
+                SpeciesId::APEX_PREDATOR,
+                fox
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_omnivore {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn hunts_then_grazes_up_to_appetite() {
+        let mut omni = Animal::omnivore();
+        omni.stats.fitness = 20.0;
+
+        let mut herb = Animal::herbivore();
+        herb.stats.weight = 10.0;
+        herb.stats.fitness = 0.01;
+        let mut herbs = vec![herb];
+
+        let eaten = omni.hunt(&mut herbs, &mut StdRng::seed_from_u64(1));
+        assert_eq!(eaten, 10.0);
+        assert!(!herbs[0].stats.alive);
+
+        let grazed = omni.graze_up_to(100.0, omni.params.f - eaten);
+        assert_eq!(grazed, 15.0);
+    }
+
+    #[test]
+    fn does_not_hunt_fitter_herbivores() {
+        let mut omni = Animal::omnivore();
+        omni.stats.fitness = 0.1;
+
+        let mut herb = Animal::herbivore();
+        herb.stats.fitness = 0.5;
+        let mut herbs = vec![herb];
+
+        let eaten = omni.hunt(&mut herbs, &mut StdRng::seed_from_u64(1));
+        assert_eq!(eaten, 0.0);
+        assert!(herbs[0].stats.alive);
+    }
+}
+
+#[cfg(test)]
+mod test_apex_predator {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn hunts_carnivores() {
+        let registry = SpeciesRegistry::default();
+        assert_eq!(
+            registry.get(SpeciesId::APEX_PREDATOR).diet.prey,
+            vec![SpeciesId::CARNIVORE]
+        );
+        assert!(!registry.get(SpeciesId::APEX_PREDATOR).diet.fodder);
+
+        let mut apex = Animal::apex_predator();
+        apex.stats.fitness = 20.0;
+
+        let mut carn = Animal::carnivore();
+        carn.stats.weight = 15.0;
+        carn.stats.fitness = 0.01;
+        let mut carns = vec![carn];
+
+        let eaten = apex.hunt(&mut carns, &mut StdRng::seed_from_u64(1));
+        assert_eq!(eaten, 15.0);
+        assert!(!carns[0].stats.alive);
+        assert_eq!(apex.stats.weight, 20.0 + 15.0 * APEX_PREDATOR.beta);
+    }
+
+    #[test]
+    fn procreates_with_own_parameters() {
+        let mut apex = Animal::new(
+            SpeciesId::APEX_PREDATOR,
+            Stats::from(5, 80.0),
+            APEX_PREDATOR,
+        );
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let newborn = (0..100)
+            .find_map(|_| apex.procreation(20, None, &mut rng))
+            .unwrap();
+
+        assert_eq!(newborn.species, SpeciesId::APEX_PREDATOR);
+        assert_eq!(newborn.params, APEX_PREDATOR);
+    }
+}
+
+#[cfg(test)]
+mod test_genetics {
+    use super::*;
+    use crate::error::BioSimError;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    fn genetics(values: &[(&str, f32)]) -> Genetics {
+        Genetics {
+            mutation: values
+                .iter()
+                .map(|&(key, sigma)| (key.to_string(), sigma))
+                .collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn newborn_inherits_mutated_traits() {
+        let genetics = genetics(&[("mu", 0.05), ("f", 2.0)]);
+        let params = HERBIVORE
+            .update(&[("gamma".to_string(), 1.0)].into())
+            .unwrap();
+        let mut herb = Animal::new(SpeciesId::HERBIVORE, Stats::from(5, 60.0), params);
+        let mut rng = StdRng::seed_from_u64(3);
+
+        let newborn = (0..100)
+            .find_map(|_| herb.procreation(50, Some(&genetics), &mut rng))
+            .unwrap();
+
+        assert_ne!(newborn.params.mu, params.mu);
+        assert_ne!(newborn.params.f, params.f);
+        assert_eq!(newborn.params.phi_age, params.phi_age);
+        assert_eq!(newborn.params.gamma, params.gamma);
+    }
+
+    #[test]
+    fn mutation_stays_in_range() {
+        let genetics = genetics(&[("eta", 10.0), ("delta_phi_max", 100.0), ("mu", 10.0)]);
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..100 {
+            let params = CARNIVORE.mutate(&genetics, &mut rng);
+            assert!((0.0..=1.0).contains(&params.eta));
+            assert!(params.delta_phi_max > 0.0);
+            assert!(params.mu >= 0.0);
+        }
+    }
+
+    #[test]
+    fn validate_genetics() {
+        assert!(genetics(&[("mu", 0.1)]).validate().is_ok());
+
+        let err = genetics(&[("speed", 0.1)]).validate().unwrap_err();
+        assert!(matches!(err, BioSimError::UnknownParameter(key) if key == "speed"));
+
+        assert!(genetics(&[("mu", -0.1)]).validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_sexual_reproduction {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn recombine_takes_traits_from_both_parents() {
+        let genetics = Genetics {
+            mutation: ["mu", "phi_age", "f", "delta_phi_max", "beta", "gamma"]
+                .iter()
+                .map(|key| (key.to_string(), 0.0))
+                .collect::<BTreeMap<_, _>>(),
+        };
+        let mut rng = StdRng::seed_from_u64(2);
+
+        let mut from_mate = 0;
+        for _ in 0..20 {
+            let params = HERBIVORE.recombine(&CARNIVORE, &genetics, &mut rng);
+            from_mate += genetics
+                .traits()
+                .filter(|key| params.get(key) == CARNIVORE.get(key))
+                .count();
+
+            assert_eq!(params.omega, HERBIVORE.omega);
+            assert_eq!(params.w_birth, HERBIVORE.w_birth);
+        }
+
+        assert!(
+            0 < from_mate && from_mate < 120,
+            "{from_mate} traits from mate"
+        );
+    }
+
+    #[test]
+    fn newborn_gets_random_sex() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let params = HERBIVORE
+            .update(&[("gamma".to_string(), 1.0)].into())
+            .unwrap();
+
+        let sexes: Vec<Sex> = (0..200)
+            .filter_map(|_| {
+                let mut herb = Animal::new(SpeciesId::HERBIVORE, Stats::from(5, 60.0), params);
+                herb.procreation_with_mate(50, &HERBIVORE, None, &mut rng)
+            })
+            .map(|newborn| newborn.stats.sex)
+            .collect();
+
+        assert!(sexes.contains(&Sex::Female));
+        assert!(sexes.contains(&Sex::Male));
     }
 }
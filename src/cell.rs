@@ -1,23 +1,73 @@
-use crate::animal::{self, AnimalTrait, Carnivore, Herbivore, Species};
-use rand::prelude::SliceRandom;
+use crate::animal::{
+    Animal, AnimalTrait, Genetics, Reproduction, Sex, SpeciesId, SpeciesRegistry, Stats,
+};
+use crate::climate::ClimateFactors;
+use crate::disease::{DiseaseParams, Infection, InfectionCounts};
+use crate::error::BioSimError;
+use crate::migration::{MigrationStrategies, Neighbourhood, Surroundings};
+use crate::terrain::{TerrainCounter, TerrainDynamics};
+use rand::{prelude::SliceRandom, Rng};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+// the animals of a cell, grouped by species
 #[derive(Clone, Debug, PartialEq)]
 pub struct Fauna {
-    pub herbivore: Vec<Herbivore>,
-    pub carnivore: Vec<Carnivore>,
+    populations: BTreeMap<SpeciesId, Vec<Animal>>,
+}
+
+impl Default for Fauna {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Fauna {
     pub const fn new() -> Fauna {
-        let herbivore = Vec::<Herbivore>::new();
-        let carnivore = Vec::<Carnivore>::new();
         Fauna {
-            herbivore,
-            carnivore,
+            populations: BTreeMap::new(),
         }
     }
+
+    pub fn get(&self, species: SpeciesId) -> &[Animal] {
+        self.populations.get(&species).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_mut(&mut self, species: SpeciesId) -> &mut Vec<Animal> {
+        self.populations.entry(species).or_default()
+    }
+
+    pub fn count(&self, species: SpeciesId) -> usize {
+        self.get(species).len()
+    }
+
+    pub fn add(&mut self, animal: Animal) {
+        self.get_mut(animal.species).push(animal);
+    }
+
+    // removes all animals of the species from the cell
+    pub fn take(&mut self, species: SpeciesId) -> Vec<Animal> {
+        self.populations.remove(&species).unwrap_or_default()
+    }
+
+    pub fn populations_mut(&mut self) -> impl Iterator<Item = &mut Vec<Animal>> {
+        self.populations.values_mut()
+    }
+
+    pub fn animals(&self) -> impl Iterator<Item = &Animal> {
+        self.populations.values().flatten()
+    }
+
+    pub fn animals_mut(&mut self) -> impl Iterator<Item = &mut Animal> {
+        self.populations.values_mut().flatten()
+    }
+
+    pub fn into_animals(self) -> impl Iterator<Item = Animal> {
+        self.populations.into_values().flatten()
+    }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
 pub enum CellType {
     Water,
     Desert,
@@ -25,6 +75,181 @@ pub enum CellType {
     Highland,
 }
 
+impl CellType {
+    // the character of the cell type in a map
+    pub fn symbol(&self) -> char {
+        match self {
+            CellType::Water => 'W',
+            CellType::Desert => 'D',
+            CellType::Lowland => 'L',
+            CellType::Highland => 'H',
+        }
+    }
+}
+
+// how the fodder left at the end of a year grows back, chosen per cell type:
+//   { model = "reset" }                                   refill to f_max every year
+//   { model = "logistic", alpha = 0.5 }                   f += alpha * f * (1 - f / f_max)
+//   { model = "seasonal", alpha = 0.3, die_off = 0.1, steps = 12 }
+// The seasonal model splits the year into steps, the first half is winter where
+// die_off of the fodder is lost every step, the second half is spring with a
+// logistic step each. Grazed down fodder regrows from at least f_min, so a bare
+// cell only recovers if f_min is above zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "model", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Regrowth {
+    #[default]
+    Reset,
+    Logistic {
+        alpha: f32,
+        #[serde(default)]
+        f_min: f32,
+    },
+    Seasonal {
+        alpha: f32,
+        die_off: f32,
+        steps: u32,
+        #[serde(default)]
+        f_min: f32,
+    },
+}
+
+impl Regrowth {
+    fn validate(&self, cell_type: CellType) -> Result<(), BioSimError> {
+        let invalid = |key, value, reason| BioSimError::InvalidParameter {
+            key: format!("{key} of {cell_type:?} regrowth"),
+            value,
+            reason,
+        };
+        let non_negative = |key, value: f32| {
+            if value.is_finite() && value >= 0.0 {
+                Ok(())
+            } else {
+                Err(invalid(key, value, "must be a non-negative number"))
+            }
+        };
+
+        match *self {
+            Regrowth::Reset => Ok(()),
+            Regrowth::Logistic { alpha, f_min } => {
+                non_negative("alpha", alpha)?;
+                non_negative("f_min", f_min)
+            }
+            Regrowth::Seasonal {
+                alpha,
+                die_off,
+                steps,
+                f_min,
+            } => {
+                non_negative("alpha", alpha)?;
+                non_negative("f_min", f_min)?;
+                if !(0.0..=1.0).contains(&die_off) {
+                    return Err(invalid("die_off", die_off, "must be between 0 and 1"));
+                }
+                if steps < 2 {
+                    return Err(invalid("steps", steps as f32, "must be at least 2"));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // the fodder at the start of the next year. A logistic step with alpha above 1
+    // would overshoot f_max, so it stops there.
+    pub fn regrow(&self, fodder: f32, f_max: f32) -> f32 {
+        let logistic = |fodder: f32, alpha: f32, f_min: f32| {
+            let fodder = fodder.max(f_min.min(f_max));
+            (fodder + alpha * fodder * (1.0 - fodder / f_max)).clamp(0.0, f_max)
+        };
+
+        if f_max <= 0.0 {
+            return 0.0;
+        }
+        match *self {
+            Regrowth::Reset => f_max,
+            Regrowth::Logistic { alpha, f_min } => logistic(fodder, alpha, f_min),
+            Regrowth::Seasonal {
+                alpha,
+                die_off,
+                steps,
+                f_min,
+            } => {
+                let winter = steps / 2;
+                let mut fodder = fodder * (1.0 - die_off).powi(winter as i32);
+                for _ in winter..steps {
+                    fodder = logistic(fodder, alpha, f_min);
+                }
+                fodder
+            }
+        }
+    }
+}
+
+// maximum amount of fodder and its regrowth per cell type
+#[derive(Clone, Debug, PartialEq)]
+pub struct LandscapeParameters {
+    f_max: HashMap<CellType, f32>,
+    regrowth: HashMap<CellType, Regrowth>,
+}
+
+impl Default for LandscapeParameters {
+    fn default() -> Self {
+        LandscapeParameters {
+            f_max: HashMap::from([
+                (CellType::Water, 0.0),
+                (CellType::Desert, 0.0),
+                (CellType::Lowland, 800.0),
+                (CellType::Highland, 300.0),
+            ]),
+            regrowth: HashMap::new(),
+        }
+    }
+}
+
+impl LandscapeParameters {
+    pub fn f_max(&self, cell_type: &CellType) -> f32 {
+        self.f_max[cell_type]
+    }
+
+    pub fn set_f_max(&mut self, cell_type: CellType, f_max: f32) -> Result<(), BioSimError> {
+        let invalid = |reason| BioSimError::InvalidParameter {
+            key: format!("f_max of {cell_type:?}"),
+            value: f_max,
+            reason,
+        };
+
+        if !matches!(cell_type, CellType::Lowland | CellType::Highland) {
+            return Err(invalid("cannot be set for a cell type without fodder"));
+        }
+        if !f_max.is_finite() || f_max < 0.0 {
+            return Err(invalid("must be a non-negative number"));
+        }
+
+        self.f_max.insert(cell_type, f_max);
+        Ok(())
+    }
+
+    pub fn regrowth(&self, cell_type: &CellType) -> Regrowth {
+        self.regrowth.get(cell_type).copied().unwrap_or_default()
+    }
+
+    pub fn set_regrowth(
+        &mut self,
+        cell_type: CellType,
+        regrowth: Regrowth,
+    ) -> Result<(), BioSimError> {
+        if !matches!(cell_type, CellType::Lowland | CellType::Highland) {
+            return Err(BioSimError::Scenario(format!(
+                "Regrowth cannot be set for {cell_type:?}, it has no fodder"
+            )));
+        }
+        regrowth.validate(cell_type)?;
+
+        self.regrowth.insert(cell_type, regrowth);
+        Ok(())
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Cell {
     name: CellType,
@@ -32,127 +257,352 @@ pub struct Cell {
     pub fauna: Option<Fauna>,
     pub fodder: f32,
     f_max: f32,
+    regrowth: Regrowth,
+    climate: ClimateFactors,
+    // the type on the map, terrain dynamics never let a cell recover beyond it
+    origin: CellType,
+    terrain: TerrainCounter,
 }
 
-impl<'a> Cell {
+impl Cell {
+    pub fn new(name: CellType, loc: (u32, u32), landscape: &LandscapeParameters) -> Cell {
+        let fauna = match name {
+            CellType::Water => None,
+            _ => Some(Fauna::new()),
+        };
+        let f_max = landscape.f_max(&name);
+
+        Cell {
+            name,
+            loc,
+            fauna,
+            fodder: f_max,
+            f_max,
+            regrowth: landscape.regrowth(&name),
+            climate: ClimateFactors::default(),
+            origin: name,
+            terrain: TerrainCounter::default(),
+        }
+    }
+
     pub fn get_cell(&self) -> CellType {
-        self.name.clone()
+        self.name
     }
 
-    pub fn add_herb_struct(&mut self, animal: Herbivore) {
-        self.fauna.as_mut().unwrap().herbivore.push(animal);
+    pub fn origin(&self) -> CellType {
+        self.origin
     }
 
-    pub fn add_carn_struct(&mut self, animal: Carnivore) {
-        self.fauna.as_mut().unwrap().carnivore.push(animal);
+    pub fn terrain(&self) -> TerrainCounter {
+        self.terrain
     }
-    // example of vec: vec![((1, 1), "Herbivore".to_string(), 200)]
-    pub fn add_animal_from(&'a mut self, species: String, amount: u32) {
-        match species.as_str() {
-            "Herbivore" => {
-                let herbivore = &mut self.fauna.as_mut().unwrap().herbivore;
-                for _ in 0..amount {
-                    let herb = animal::Herbivore::new();
-                    herbivore.push(herb);
-                }
-            }
-            "Carnivore" => {
-                let carnivore = &mut self.fauna.as_mut().unwrap().carnivore;
-                for _ in 0..amount {
-                    let carn = animal::Carnivore::new();
-                    carnivore.push(carn);
-                }
+
+    // the fodder the animals that graze would eat this year if there was enough
+    pub fn grazing_demand(&self, registry: &SpeciesRegistry) -> f32 {
+        self.fauna.as_ref().map_or(0.0, |fauna| {
+            fauna
+                .animals()
+                .filter(|animal| registry.get(animal.species).diet.fodder)
+                .map(|animal| animal.params().f)
+                .sum()
+        })
+    }
+
+    // counts towards degradation or recovery, given the grazing demand before feeding
+    pub fn record_grazing(&mut self, dynamics: &TerrainDynamics, demand: f32) {
+        let f_max = self.current_f_max();
+        dynamics.record(&mut self.terrain, f_max, demand, self.fodder);
+    }
+
+    // turns the cell into another type with that type's f_max and regrowth, keeping
+    // the fodder up to the new f_max. Turning into water returns the animals of the
+    // cell, which the caller has to place elsewhere.
+    pub fn set_cell_type(
+        &mut self,
+        cell_type: CellType,
+        landscape: &LandscapeParameters,
+    ) -> Vec<Animal> {
+        self.name = cell_type;
+        self.terrain = TerrainCounter::default();
+        self.f_max = landscape.f_max(&cell_type);
+        self.regrowth = landscape.regrowth(&cell_type);
+        self.fodder = self.fodder.min(self.current_f_max());
+
+        match cell_type {
+            CellType::Water => self
+                .fauna
+                .take()
+                .map(|fauna| fauna.into_animals().collect())
+                .unwrap_or_default(),
+            _ => {
+                self.fauna.get_or_insert_with(Fauna::new);
+                Vec::new()
             }
-            name => panic!("Wrong species name: {name}"),
         }
     }
 
-    // sort
-    pub fn sort_herbivore_after_fitness(&mut self, descending: bool) {
-        self.fauna.as_mut().unwrap().herbivore.sort_by(|a, b| {
-            if descending {
-                b.stats.fitness.partial_cmp(&a.stats.fitness).unwrap()
-            } else {
-                a.stats.fitness.partial_cmp(&b.stats.fitness).unwrap()
-            }
-        });
+    pub fn f_max(&self) -> f32 {
+        self.f_max
+    }
+
+    // takes effect at the next reset_fodder or regrow_fodder
+    pub fn set_f_max(&mut self, f_max: f32) {
+        self.f_max = f_max;
     }
 
-    // feed animals
-    pub fn feed_animals(&mut self) {
-        if !self.fauna.as_ref().unwrap().herbivore.is_empty() {
-            self.sort_herbivore_after_fitness(false);
-            let fauna = &mut self.fauna.as_mut().unwrap();
-            let herbivores = &mut fauna.herbivore;
+    // f_max scaled by the climate events active this year
+    pub fn current_f_max(&self) -> f32 {
+        self.f_max * self.climate.f_max
+    }
+
+    pub fn climate(&self) -> ClimateFactors {
+        self.climate
+    }
+
+    // fodder above the scaled f_max is lost right away, when an event ends the
+    // limit is only raised for the next regrowth
+    pub fn set_climate(&mut self, climate: ClimateFactors) {
+        self.climate = climate;
+        self.fodder = self.fodder.min(self.current_f_max());
+    }
+
+    pub fn regrowth(&self) -> Regrowth {
+        self.regrowth
+    }
+
+    pub fn set_regrowth(&mut self, regrowth: Regrowth) {
+        self.regrowth = regrowth;
+    }
+
+    pub fn add_animal(&mut self, animal: Animal) {
+        self.fauna.as_mut().unwrap().add(animal);
+    }
+
+    // example of vec: vec![((1, 1), "Herbivore".to_string(), 200)]
+    pub fn add_animal_from(
+        &mut self,
+        species: String,
+        amount: u32,
+        registry: &SpeciesRegistry,
+    ) -> Result<(), BioSimError> {
+        self.add_animals(&species, Stats::new_default(), amount, registry)
+    }
+
+    // add amount animals that start out with the given stats, split evenly between
+    // females and males with one more female for an odd amount
+    pub fn add_animals(
+        &mut self,
+        species: &str,
+        stats: Stats,
+        amount: u32,
+        registry: &SpeciesRegistry,
+    ) -> Result<(), BioSimError> {
+        let females = amount.div_ceil(2);
+        let female = Stats {
+            sex: Sex::Female,
+            ..stats.clone()
+        };
+        self.add_identical_animals(species, female, females, registry)?;
+        let male = Stats {
+            sex: Sex::Male,
+            ..stats
+        };
+        self.add_identical_animals(species, male, amount - females, registry)
+    }
+
+    // add amount animals that all start out with the given stats, sex included
+    pub fn add_identical_animals(
+        &mut self,
+        species: &str,
+        stats: Stats,
+        amount: u32,
+        registry: &SpeciesRegistry,
+    ) -> Result<(), BioSimError> {
+        let fauna = self
+            .fauna
+            .as_mut()
+            .ok_or(BioSimError::AnimalsInWater(self.loc))?;
+
+        let species = registry.id(species)?;
+        let params = *registry.params(species);
+        let animals = fauna.get_mut(species);
+        for _ in 0..amount {
+            animals.push(Animal::new(species, stats.clone(), params));
+        }
+
+        Ok(())
+    }
 
-            for herb in herbivores {
-                if self.fodder > 0.0 {
-                    self.fodder -= herb.feeding(self.fodder);
+    // sort
+    pub fn sort_after_fitness(&mut self, species: SpeciesId, descending: bool) {
+        self.fauna
+            .as_mut()
+            .unwrap()
+            .get_mut(species)
+            .sort_by(|a, b| {
+                if descending {
+                    b.stats.fitness.partial_cmp(&a.stats.fitness).unwrap()
                 } else {
-                    break;
+                    a.stats.fitness.partial_cmp(&b.stats.fitness).unwrap()
                 }
+            });
+    }
+
+    // species feed one after the other, in the registry's feeding order
+    pub fn feed_animals(&mut self, registry: &SpeciesRegistry, rng: &mut impl Rng) {
+        for species in registry.feeding_order() {
+            if self.fauna.as_ref().unwrap().count(species) == 0 {
+                continue;
+            }
+
+            let diet = &registry.get(species).diet;
+            if !diet.prey.is_empty() {
+                self.hunt(species, &diet.prey, diet.fodder, rng);
+            } else if diet.fodder {
+                self.graze(species);
             }
         }
+    }
+
+    // the animals with the lowest fitness eat first, until the fodder runs out
+    fn graze(&mut self, species: SpeciesId) {
+        self.sort_after_fitness(species, false);
+        let animals = self.fauna.as_mut().unwrap().get_mut(species);
 
-        if !self.fauna.as_ref().unwrap().carnivore.is_empty() {
-            self.sort_herbivore_after_fitness(false);
+        for animal in animals {
+            if self.fodder > 0.0 {
+                self.fodder -= animal.graze(self.fodder);
+            } else {
+                break;
+            }
+        }
+    }
 
-            let fauna = &mut self.fauna.as_mut().unwrap();
+    // the hunters go in random order, each trying the weakest prey first. Hunters that
+    // also graze top up from the fodder right after hunting, up to their appetite.
+    fn hunt(&mut self, species: SpeciesId, prey: &[SpeciesId], grazes: bool, rng: &mut impl Rng) {
+        let fauna = self.fauna.as_mut().unwrap();
 
-            let herbivores = &mut fauna.herbivore;
+        let mut hunters = fauna.take(species);
+        let mut prey_animals: Vec<Animal> = prey.iter().flat_map(|&id| fauna.take(id)).collect();
+        prey_animals.sort_by(|a, b| a.stats.fitness.partial_cmp(&b.stats.fitness).unwrap());
 
-            let carnivores = &mut fauna.carnivore;
-            // shuffle carnivores
-            carnivores.shuffle(&mut rand::thread_rng());
+        // shuffle hunters
+        hunters.shuffle(rng);
 
-            for carnivore in carnivores {
-                carnivore.feeding(herbivores);
+        for hunter in hunters.iter_mut() {
+            let amount_eaten = hunter.hunt(&mut prey_animals, rng);
 
-                // remove dead herbivores
-                herbivores.retain(|herb| herb.stats.alive);
+            let appetite = hunter.params().f - amount_eaten;
+            if grazes && self.fodder > 0.0 && appetite > 0.0 {
+                self.fodder -= hunter.graze_up_to(self.fodder, appetite);
             }
+
+            // remove dead prey
+            prey_animals.retain(|animal| animal.stats.alive);
         }
+
+        prey_animals
+            .into_iter()
+            .for_each(|animal| fauna.add(animal));
+        fauna.get_mut(species).extend(hunters);
     }
 
     pub fn reset_fodder(&mut self) {
-        self.fodder = self.f_max;
+        self.fodder = self.current_f_max();
     }
 
-    pub fn animal_death(&mut self) {
-        let fauna = &mut self.fauna.as_mut().unwrap();
-        let herbivores = &mut fauna.herbivore;
-        let carnivores = &mut fauna.carnivore;
+    // end of year regrowth of what the animals left, following the cell's model
+    pub fn regrow_fodder(&mut self) {
+        self.fodder = self.regrowth.regrow(self.fodder, self.current_f_max());
+    }
 
-        herbivores.retain(|herb| herb.stats.alive);
-        carnivores.retain(|carn| carn.stats.alive);
+    // natural mortality, with omega scaled by the climate, then the animals that died
+    // this year are removed
+    pub fn animal_death(&mut self, rng: &mut impl Rng) {
+        let omega_factor = self.climate.omega;
+
+        for animals in self.fauna.as_mut().unwrap().populations_mut() {
+            animals
+                .iter_mut()
+                .filter(|animal| animal.stats.alive)
+                .for_each(|animal| animal.death(omega_factor, rng));
+            animals.retain(|animal| animal.stats.alive);
+        }
     }
 
-    pub fn age_animals(&mut self) {
-        let fauna = &mut self.fauna.as_mut().unwrap();
-        let herbivores = &mut fauna.herbivore;
-        let carnivores = &mut fauna.carnivore;
+    // infected animals suffer and may recover before the disease spreads, the
+    // chance of infection depends on the number infected at the start of the step
+    pub fn spread_disease(
+        &mut self,
+        disease: &BTreeMap<SpeciesId, DiseaseParams>,
+        rng: &mut impl Rng,
+    ) {
+        let fauna = self.fauna.as_mut().unwrap();
+
+        for (&species, params) in disease {
+            let animals = fauna.get_mut(species);
+            let infected = animals
+                .iter()
+                .filter(|animal| animal.stats.infection == Infection::Infected)
+                .count();
+            if infected == 0 {
+                continue;
+            }
 
-        // age herbivores in a fucntional way
-        herbivores.iter_mut().for_each(|herb| herb.aging());
-        carnivores.iter_mut().for_each(|carn| carn.aging());
+            let probability = params.infection_probability(infected);
+            for animal in animals.iter_mut() {
+                match animal.stats.infection {
+                    Infection::Infected => animal.sickness(params, rng),
+                    _ => animal.infect(probability, rng),
+                }
+            }
+        }
     }
 
-    pub fn loss_of_weight(&mut self) {
-        let fauna = &mut self.fauna.as_mut().unwrap();
-        let herbivores = &mut fauna.herbivore;
-        let carnivores = &mut fauna.carnivore;
+    pub fn infection_counts(&self, species: SpeciesId) -> InfectionCounts {
+        let mut counts = InfectionCounts::default();
+        if let Some(fauna) = &self.fauna {
+            fauna
+                .get(species)
+                .iter()
+                .for_each(|animal| counts.add(animal.stats.infection));
+        }
+        counts
+    }
 
-        herbivores.iter_mut().for_each(|herb| herb.loss_of_weight());
-        carnivores.iter_mut().for_each(|carn| carn.loss_of_weight());
+    pub fn age_animals(&mut self) {
+        // age animals in a fucntional way
+        self.fauna
+            .as_mut()
+            .unwrap()
+            .animals_mut()
+            .for_each(|animal| animal.aging());
     }
 
-    pub fn get_random_neighboring_cell(loc: (u32, u32)) -> Option<(u32, u32)> {
-        let mut rng = rand::thread_rng();
+    pub fn loss_of_weight(&mut self) {
+        self.fauna
+            .as_mut()
+            .unwrap()
+            .animals_mut()
+            .for_each(|animal| animal.loss_of_weight());
+    }
 
+    pub fn get_random_neighboring_cell(
+        loc: (u32, u32),
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Option<(u32, u32)> {
         // move north, east, south, west
-        let directions = vec![(0, 1), (1, 0), (0, -1), (-1, 0)];
+        Cell::get_random_cell_in(loc, &Neighbourhood::VonNeumann.offsets(), rng)
+    }
 
-        let direction = directions.choose(&mut rng).unwrap();
+    // loc moved by one of the offsets, None when that leaves the map
+    pub fn get_random_cell_in(
+        loc: (u32, u32),
+        offsets: &[(i32, i32)],
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Option<(u32, u32)> {
+        let direction = offsets.choose(rng)?;
 
         let x = loc.0 as i32 + direction.0;
         let y = loc.1 as i32 + direction.1;
@@ -164,131 +614,119 @@ impl<'a> Cell {
         Some((x as u32, y as u32))
     }
 
-    pub fn get_moving_animals(&mut self) {
-        let current_loc = self.loc.clone();
-
-        for herb in self.fauna.as_mut().unwrap().herbivore.iter_mut() {
-            if herb.migrate() {
-                let loc_option: Option<(u32, u32)>;
-                if let Some(new_loc) = Cell::get_random_neighboring_cell(current_loc) {
-                    loc_option = Some(new_loc);
-                } else {
-                    loc_option = None;
-                }
-                herb.stats_as_mut().move_to = loc_option;
+    // the species' strategy picks where each animal that leaves goes
+    pub fn get_moving_animals(
+        &mut self,
+        strategies: &MigrationStrategies,
+        surroundings: &Surroundings,
+        rng: &mut impl Rng,
+    ) {
+        let current_loc = self.loc;
+
+        for animal in self.fauna.as_mut().unwrap().animals_mut() {
+            if animal.migrate(rng) {
+                let strategy = strategies.get(animal.species);
+                animal.stats_as_mut().move_to =
+                    strategy.destination(animal, current_loc, surroundings, rng);
             }
         }
+    }
 
-        for carn in self.fauna.as_mut().unwrap().carnivore.iter_mut() {
-            if carn.migrate() {
-                let loc_option;
-                if let Some(new_loc) = Cell::get_random_neighboring_cell(current_loc) {
-                    loc_option = Some(new_loc);
-                } else {
-                    loc_option = None;
-                }
-                carn.stats_as_mut().move_to = loc_option;
-            }
+    pub fn add_newborns(
+        &mut self,
+        genetics: Option<&Genetics>,
+        reproduction: Reproduction,
+        rng: &mut impl Rng,
+    ) {
+        for animals in self.fauna.as_mut().expect("FAUNE").populations_mut() {
+            let newborns = match reproduction {
+                Reproduction::Asexual => Cell::asexual_births(animals, genetics, rng),
+                Reproduction::Sexual => Cell::sexual_births(animals, genetics, rng),
+            };
+            animals.extend(newborns);
         }
     }
 
-    pub fn add_newborns(&mut self) {
-        let herbs = &mut self.fauna.as_mut().expect("FAUNE").herbivore;
+    fn asexual_births(
+        animals: &mut [Animal],
+        genetics: Option<&Genetics>,
+        rng: &mut impl Rng,
+    ) -> Vec<Animal> {
         let mut newborns = Vec::new();
 
-        let herb_count = herbs.len();
+        let count = animals.len();
 
-        for herb in herbs.iter_mut() {
-            if let Some(newborn) = herb.procreation(herb_count) {
+        for animal in animals.iter_mut() {
+            if let Some(newborn) = animal.procreation(count, genetics, rng) {
                 newborns.push(newborn);
             }
         }
-        herbs.extend(newborns);
 
-        let carns = &mut self.fauna.as_mut().unwrap().carnivore;
+        newborns
+    }
+
+    // every female tries to give birth with the next male that has not fathered a
+    // newborn yet this year
+    fn sexual_births(
+        animals: &mut [Animal],
+        genetics: Option<&Genetics>,
+        rng: &mut impl Rng,
+    ) -> Vec<Animal> {
         let mut newborns = Vec::new();
 
-        let carn_count = carns.len();
+        let count = animals.len();
+        let mut fathers = (0..count)
+            .filter(|&i| animals[i].stats.sex == Sex::Male)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .peekable();
+
+        for i in 0..count {
+            if animals[i].stats.sex != Sex::Female {
+                continue;
+            }
+            let Some(&father) = fathers.peek() else {
+                break;
+            };
 
-        for carn in carns.iter_mut() {
-            if let Some(newborn) = carn.procreation(carn_count) {
+            let mate = animals[father].params;
+            if let Some(newborn) = animals[i].procreation_with_mate(count, &mate, genetics, rng) {
                 newborns.push(newborn);
+                fathers.next();
             }
         }
+
+        newborns
     }
 }
 
 pub fn water(loc: (u32, u32)) -> Cell {
-    Cell {
-        name: CellType::Water,
-        loc,
-        fauna: None,
-        fodder: 0.0,
-        f_max: 0.0,
-    }
+    Cell::new(CellType::Water, loc, &LandscapeParameters::default())
 }
 
 pub fn desert(loc: (u32, u32)) -> Cell {
-    Cell {
-        name: CellType::Desert,
-        fauna: Some(Fauna::new()),
-        loc,
-        fodder: 0.0,
-        f_max: 0.0,
-    }
+    Cell::new(CellType::Desert, loc, &LandscapeParameters::default())
 }
 
 pub fn lowland(loc: (u32, u32)) -> Cell {
-    Cell {
-        name: CellType::Lowland,
-        fauna: Some(Fauna::new()),
-        loc,
-        fodder: 800.0,
-        f_max: 800.0,
-    }
+    Cell::new(CellType::Lowland, loc, &LandscapeParameters::default())
 }
 
 pub fn highland(loc: (u32, u32)) -> Cell {
-    Cell {
-        name: CellType::Highland,
-        fauna: Some(Fauna::new()),
-        loc,
-        fodder: 300.0,
-        f_max: 300.0,
-    }
+    Cell::new(CellType::Highland, loc, &LandscapeParameters::default())
 }
 
-pub fn from_char(c: char, loc: (u32, u32)) -> Cell {
+pub fn from_char(c: char, loc: (u32, u32)) -> Result<Cell, BioSimError> {
     match c {
-        'W' => Cell {
-            name: CellType::Water,
-            loc,
-            fauna: None,
-            fodder: 0.0,
-            f_max: 0.0,
-        },
-        'D' => Cell {
-            name: CellType::Desert,
-            fauna: Some(Fauna::new()),
-            loc,
-            fodder: 0.0,
-            f_max: 0.0,
-        },
-        'L' => Cell {
-            name: CellType::Lowland,
-            loc,
-            fauna: Some(Fauna::new()),
-            fodder: 800.0,
-            f_max: 800.0,
-        },
-        'H' => Cell {
-            name: CellType::Highland,
-            loc,
-            fauna: Some(Fauna::new()),
-            fodder: 300.0,
-            f_max: 300.0,
-        },
-        _ => panic!(),
+        'W' => Ok(water(loc)),
+        'D' => Ok(desert(loc)),
+        'L' => Ok(lowland(loc)),
+        'H' => Ok(highland(loc)),
+        ch => Err(BioSimError::InvalidCell {
+            line: loc.1 as usize + 1,
+            col: loc.0 as usize + 1,
+            ch,
+        }),
     }
 }
 
@@ -299,14 +737,14 @@ mod test_cell_creation {
     #[test]
     fn test_herb_struct() {
         let mut cell = lowland((1, 1));
-        cell.add_carn_struct(animal::Carnivore::new());
-        cell.add_carn_struct(animal::Carnivore::new());
-        cell.add_herb_struct(animal::Herbivore::new());
+        cell.add_animal(Animal::carnivore());
+        cell.add_animal(Animal::carnivore());
+        cell.add_animal(Animal::herbivore());
 
         println!("{:#?}", cell.fauna);
 
-        assert_eq!(cell.fauna.as_ref().unwrap().herbivore.len(), 1);
-        assert_eq!(cell.fauna.as_ref().unwrap().carnivore.len(), 2);
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE), 1);
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::CARNIVORE), 2);
     }
 
     #[test]
@@ -335,29 +773,57 @@ mod test_cell_creation {
 
     #[test]
     fn test_from_char() {
-        let cell = from_char('W', (1, 1));
+        let cell = from_char('W', (1, 1)).unwrap();
         assert_eq!(cell.get_cell(), CellType::Water);
     }
+
+    #[test]
+    fn test_from_invalid_char() {
+        let err = from_char('E', (2, 0)).unwrap_err();
+        assert!(matches!(
+            err,
+            BioSimError::InvalidCell {
+                line: 1,
+                col: 3,
+                ch: 'E'
+            }
+        ));
+    }
+
+    #[test]
+    fn test_add_animals_errors() {
+        let params = SpeciesRegistry::default();
+
+        let mut cell = lowland((1, 1));
+        let err = cell.add_animal_from("Unicorn".to_string(), 1, &params);
+        assert!(matches!(err, Err(BioSimError::UnknownSpecies(name)) if name == "Unicorn"));
+
+        let mut cell = water((0, 0));
+        let err = cell.add_animal_from("Herbivore".to_string(), 1, &params);
+        assert!(matches!(err, Err(BioSimError::AnimalsInWater((0, 0)))));
+    }
 }
 
 #[cfg(test)]
 mod test_cell_methods {
     use super::*;
+    use crate::animal::{SpeciesEntry, HERBIVORE};
+    use rand::{rngs::StdRng, SeedableRng};
 
-    // test sort_herbivore_after_fitness
+    // test sort_after_fitness
     #[test]
-    fn test_sort_herbivore_after_fitness() {
+    fn test_sort_after_fitness() {
         let mut cell = lowland((1, 1));
-        let herb1 = Herbivore::new();
-        let herb2 = Herbivore::new();
-        let herb3 = Herbivore::new();
-        let herb4 = Herbivore::new();
-        let herb5 = Herbivore::new();
-        let herb6 = Herbivore::new();
-        let herb7 = Herbivore::new();
-        let herb8 = Herbivore::new();
-        let herb9 = Herbivore::new();
-        let herb10 = Herbivore::new();
+        let herb1 = Animal::herbivore();
+        let herb2 = Animal::herbivore();
+        let herb3 = Animal::herbivore();
+        let herb4 = Animal::herbivore();
+        let herb5 = Animal::herbivore();
+        let herb6 = Animal::herbivore();
+        let herb7 = Animal::herbivore();
+        let herb8 = Animal::herbivore();
+        let herb9 = Animal::herbivore();
+        let herb10 = Animal::herbivore();
 
         let mut herb_vec = vec![
             herb1, herb2, herb3, herb4, herb5, herb6, herb7, herb8, herb9, herb10,
@@ -367,16 +833,22 @@ mod test_cell_methods {
             herb.stats.fitness = i as f32;
         }
 
-        cell.fauna.as_mut().unwrap().herbivore = herb_vec;
+        *cell.fauna.as_mut().unwrap().get_mut(SpeciesId::HERBIVORE) = herb_vec;
 
-        println!("{:#?}", cell.fauna.as_ref().unwrap().herbivore);
+        println!(
+            "{:#?}",
+            cell.fauna.as_ref().unwrap().get(SpeciesId::HERBIVORE)
+        );
 
-        cell.sort_herbivore_after_fitness(true);
+        cell.sort_after_fitness(SpeciesId::HERBIVORE, true);
 
         println!("--------------------------------------------------------");
-        println!("{:#?}", cell.fauna.as_ref().unwrap().herbivore);
+        println!(
+            "{:#?}",
+            cell.fauna.as_ref().unwrap().get(SpeciesId::HERBIVORE)
+        );
 
-        let sorted_herb = &cell.fauna.as_ref().unwrap().herbivore;
+        let sorted_herb = cell.fauna.as_ref().unwrap().get(SpeciesId::HERBIVORE);
 
         for i in 0..sorted_herb.len() - 1 {
             assert!(
@@ -390,24 +862,24 @@ mod test_cell_methods {
     #[test]
     fn test_feed_herbivores() {
         let mut cell = lowland((1, 1));
-        let herb1 = Herbivore::new();
-        let herb2 = Herbivore::new();
-        let herb3 = Herbivore::new();
-        let herb4 = Herbivore::new();
-        let herb5 = Herbivore::new();
-        let herb6 = Herbivore::new();
-        let herb7 = Herbivore::new();
-        let herb8 = Herbivore::new();
-        let herb9 = Herbivore::new();
-        let herb10 = Herbivore::new();
+        let herb1 = Animal::herbivore();
+        let herb2 = Animal::herbivore();
+        let herb3 = Animal::herbivore();
+        let herb4 = Animal::herbivore();
+        let herb5 = Animal::herbivore();
+        let herb6 = Animal::herbivore();
+        let herb7 = Animal::herbivore();
+        let herb8 = Animal::herbivore();
+        let herb9 = Animal::herbivore();
+        let herb10 = Animal::herbivore();
 
         let herb_vec = vec![
             herb1, herb2, herb3, herb4, herb5, herb6, herb7, herb8, herb9, herb10,
         ];
 
-        cell.fauna.as_mut().unwrap().herbivore = herb_vec;
+        *cell.fauna.as_mut().unwrap().get_mut(SpeciesId::HERBIVORE) = herb_vec;
 
-        cell.feed_animals();
+        cell.feed_animals(&SpeciesRegistry::default(), &mut StdRng::seed_from_u64(1));
 
         assert_ne!(cell.fodder, 800.0);
     }
@@ -416,55 +888,74 @@ mod test_cell_methods {
     #[test]
     fn test_feed_carnivores() {
         let mut cell = lowland((1, 1));
-        let herb1 = Herbivore::new();
-        let herb2 = Herbivore::new();
-        let herb3 = Herbivore::new();
-        let herb4 = Herbivore::new();
-        let herb5 = Herbivore::new();
-        let herb6 = Herbivore::new();
-        let herb7 = Herbivore::new();
-        let herb8 = Herbivore::new();
-        let herb9 = Herbivore::new();
-        let herb10 = Herbivore::new();
+        let herb1 = Animal::herbivore();
+        let herb2 = Animal::herbivore();
+        let herb3 = Animal::herbivore();
+        let herb4 = Animal::herbivore();
+        let herb5 = Animal::herbivore();
+        let herb6 = Animal::herbivore();
+        let herb7 = Animal::herbivore();
+        let herb8 = Animal::herbivore();
+        let herb9 = Animal::herbivore();
+        let herb10 = Animal::herbivore();
 
         let herb_vec = vec![
             herb1, herb2, herb3, herb4, herb5, herb6, herb7, herb8, herb9, herb10,
         ];
 
-        let carn1 = Carnivore::new();
-        let carn2 = Carnivore::new();
-        let carn3 = Carnivore::new();
-        let carn4 = Carnivore::new();
-        let carn5 = Carnivore::new();
-        let carn6 = Carnivore::new();
-        let carn7 = Carnivore::new();
-        let carn8 = Carnivore::new();
-        let carn9 = Carnivore::new();
-        let carn10 = Carnivore::new();
+        let carn1 = Animal::carnivore();
+        let carn2 = Animal::carnivore();
+        let carn3 = Animal::carnivore();
+        let carn4 = Animal::carnivore();
+        let carn5 = Animal::carnivore();
+        let carn6 = Animal::carnivore();
+        let carn7 = Animal::carnivore();
+        let carn8 = Animal::carnivore();
+        let carn9 = Animal::carnivore();
+        let carn10 = Animal::carnivore();
 
         let carn_vec = vec![
             carn1, carn2, carn3, carn4, carn5, carn6, carn7, carn8, carn9, carn10,
         ];
 
-        cell.fauna.as_mut().unwrap().herbivore = herb_vec;
-        cell.fauna.as_mut().unwrap().carnivore = carn_vec;
+        *cell.fauna.as_mut().unwrap().get_mut(SpeciesId::HERBIVORE) = herb_vec;
+        *cell.fauna.as_mut().unwrap().get_mut(SpeciesId::CARNIVORE) = carn_vec;
 
-        cell.feed_animals();
+        cell.feed_animals(&SpeciesRegistry::default(), &mut StdRng::seed_from_u64(1));
 
         println!(
             "len of hebivores: {:#?}",
-            cell.fauna.as_ref().unwrap().herbivore.len()
+            cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE)
         );
 
-        println!("{:#?}", cell.fauna.as_ref().unwrap().carnivore);
+        println!(
+            "{:#?}",
+            cell.fauna.as_ref().unwrap().get(SpeciesId::CARNIVORE)
+        );
 
         assert!(
-            cell.fauna.as_ref().unwrap().herbivore.len() < 10,
+            cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE) < 10,
             "No herb died"
         );
     }
 
     // test reset fodder
+    #[test]
+    fn test_natural_mortality() {
+        let registry = SpeciesRegistry::default();
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::from(5, 20.0), 500, &registry)
+            .unwrap();
+        let mut starved = Animal::herbivore();
+        starved.stats.weight = 0.0;
+        cell.add_animal(starved);
+
+        cell.animal_death(&mut StdRng::seed_from_u64(1));
+
+        let survivors = cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE);
+        assert!(survivors > 300 && survivors < 500, "{survivors}");
+    }
+
     #[test]
     fn test_reset_fodder() {
         let mut cell = lowland((1, 1));
@@ -473,4 +964,375 @@ mod test_cell_methods {
 
         assert_eq!(cell.fodder, 800.0);
     }
+
+    #[test]
+    fn test_landscape_parameters() {
+        let mut landscape = LandscapeParameters::default();
+        landscape.set_f_max(CellType::Highland, 150.0).unwrap();
+
+        let cell = Cell::new(CellType::Highland, (1, 1), &landscape);
+        assert_eq!(cell.fodder, 150.0);
+
+        assert!(landscape.set_f_max(CellType::Water, 10.0).is_err());
+        assert!(landscape.set_f_max(CellType::Lowland, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_set_f_max_applies_at_reset() {
+        let mut cell = lowland((1, 1));
+        cell.set_f_max(500.0);
+        assert_eq!(cell.fodder, 800.0);
+
+        cell.reset_fodder();
+        assert_eq!(cell.fodder, 500.0);
+    }
+
+    #[test]
+    fn test_climate_scales_fodder() {
+        let mut cell = lowland((1, 1));
+        cell.set_climate(ClimateFactors {
+            f_max: 0.25,
+            omega: 1.0,
+        });
+        assert_eq!(cell.fodder, 200.0);
+
+        cell.fodder = 0.0;
+        cell.reset_fodder();
+        assert_eq!(cell.fodder, 200.0);
+        assert_eq!(cell.f_max(), 800.0);
+
+        cell.set_climate(ClimateFactors::default());
+        assert_eq!(cell.fodder, 200.0);
+        cell.reset_fodder();
+        assert_eq!(cell.fodder, 800.0);
+    }
+
+    #[test]
+    fn test_climate_raises_mortality() {
+        let registry = SpeciesRegistry::default();
+        let survivors = |omega: f32| {
+            let mut cell = lowland((1, 1));
+            cell.set_climate(ClimateFactors { f_max: 1.0, omega });
+            cell.add_animals("Herbivore", Stats::from(5, 20.0), 500, &registry)
+                .unwrap();
+            cell.animal_death(&mut StdRng::seed_from_u64(1));
+            cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE)
+        };
+
+        let normal = survivors(1.0);
+        let harsh = survivors(3.0);
+        assert!(normal < 500, "nobody died");
+        assert!(500 - harsh > 2 * (500 - normal), "{normal} vs {harsh}");
+    }
+
+    #[test]
+    fn test_set_cell_type() {
+        let landscape = LandscapeParameters::default();
+        let registry = SpeciesRegistry::default();
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::new_default(), 3, &registry)
+            .unwrap();
+
+        let displaced = cell.set_cell_type(CellType::Water, &landscape);
+        assert_eq!(displaced.len(), 3);
+        assert!(cell.fauna.is_none());
+        assert_eq!(cell.fodder, 0.0);
+
+        let displaced = cell.set_cell_type(CellType::Lowland, &landscape);
+        assert!(displaced.is_empty());
+        assert_eq!(cell.fauna, Some(Fauna::new()));
+        assert_eq!(cell.f_max(), 800.0);
+        assert_eq!(cell.fodder, 0.0);
+    }
+
+    #[test]
+    fn test_logistic_regrowth() {
+        let regrowth = Regrowth::Logistic {
+            alpha: 0.5,
+            f_min: 0.0,
+        };
+        assert_eq!(regrowth.regrow(400.0, 800.0), 500.0);
+        assert_eq!(regrowth.regrow(800.0, 800.0), 800.0);
+        assert_eq!(regrowth.regrow(0.0, 800.0), 0.0);
+
+        let regrowth = Regrowth::Logistic {
+            alpha: 0.5,
+            f_min: 100.0,
+        };
+        assert!(regrowth.regrow(0.0, 800.0) > 100.0);
+
+        let regrowth = Regrowth::Logistic {
+            alpha: 2.5,
+            f_min: 0.0,
+        };
+        assert_eq!(regrowth.regrow(400.0, 800.0), 800.0);
+        assert_eq!(regrowth.regrow(100.0, 800.0), 100.0 + 2.5 * 100.0 * 0.875);
+
+        let regrowth = Regrowth::Seasonal {
+            alpha: 2.5,
+            die_off: 0.0,
+            steps: 6,
+            f_min: 0.0,
+        };
+        assert_eq!(regrowth.regrow(400.0, 800.0), 800.0);
+    }
+
+    #[test]
+    fn test_seasonal_regrowth() {
+        let regrowth = Regrowth::Seasonal {
+            alpha: 0.4,
+            die_off: 0.5,
+            steps: 4,
+            f_min: 0.0,
+        };
+        // two winter steps leave a quarter, then two spring steps
+        let after_winter = 100.0;
+        let spring = after_winter + 0.4 * after_winter * (1.0 - after_winter / 800.0);
+        let spring = spring + 0.4 * spring * (1.0 - spring / 800.0);
+        assert_eq!(regrowth.regrow(400.0, 800.0), spring);
+    }
+
+    #[test]
+    fn test_regrowth_carries_over() {
+        let mut landscape = LandscapeParameters::default();
+        landscape
+            .set_regrowth(
+                CellType::Lowland,
+                Regrowth::Logistic {
+                    alpha: 0.5,
+                    f_min: 0.0,
+                },
+            )
+            .unwrap();
+
+        let mut cell = Cell::new(CellType::Lowland, (1, 1), &landscape);
+        cell.fodder = 200.0;
+        cell.regrow_fodder();
+        assert!(cell.fodder < 800.0, "{}", cell.fodder);
+
+        let mut cell = lowland((1, 1));
+        cell.fodder = 200.0;
+        cell.regrow_fodder();
+        assert_eq!(cell.fodder, 800.0);
+
+        assert!(landscape
+            .set_regrowth(CellType::Desert, Regrowth::Reset)
+            .is_err());
+        assert!(landscape
+            .set_regrowth(
+                CellType::Highland,
+                Regrowth::Seasonal {
+                    alpha: 0.5,
+                    die_off: 1.5,
+                    steps: 4,
+                    f_min: 0.0
+                }
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_feed_custom_predator() {
+        let mut registry = SpeciesRegistry::default();
+        let fox = registry
+            .register_entry(&SpeciesEntry {
+                name: "Fox".to_string(),
+                display_name: None,
+                based_on: "Carnivore".to_string(),
+                params: HashMap::new(),
+                fodder: None,
+                prey: Some(vec!["Herbivore".to_string()]),
+            })
+            .unwrap();
+
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::from(50, 5.0), 10, &registry)
+            .unwrap();
+        cell.add_animals("Fox", Stats::from(2, 40.0), 5, &registry)
+            .unwrap();
+
+        cell.feed_animals(&registry, &mut StdRng::seed_from_u64(1));
+
+        let fauna = cell.fauna.as_ref().unwrap();
+        assert!(fauna.count(SpeciesId::HERBIVORE) < 10, "No herb was eaten");
+        assert_eq!(fauna.count(fox), 5);
+        assert_eq!(fauna.count(SpeciesId::CARNIVORE), 0);
+    }
+
+    #[test]
+    fn test_feed_omnivores() {
+        let registry = SpeciesRegistry::default();
+
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::from(50, 5.0), 10, &registry)
+            .unwrap();
+        cell.add_animals("Omnivore", Stats::from(2, 30.0), 5, &registry)
+            .unwrap();
+
+        cell.feed_animals(&registry, &mut StdRng::seed_from_u64(1));
+
+        let fauna = cell.fauna.as_ref().unwrap();
+        assert!(fauna.count(SpeciesId::HERBIVORE) < 10, "No herb was eaten");
+        assert_eq!(fauna.count(SpeciesId::OMNIVORE), 5);
+
+        // the herbivores grazed 10 each, omnivores topped up what hunting left
+        assert!(cell.fodder < 700.0, "{}", cell.fodder);
+    }
+
+    #[test]
+    fn test_feed_apex_predators() {
+        let registry = SpeciesRegistry::default();
+
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Carnivore", Stats::from(50, 5.0), 10, &registry)
+            .unwrap();
+        cell.add_animals("ApexPredator", Stats::from(2, 40.0), 3, &registry)
+            .unwrap();
+
+        cell.feed_animals(&registry, &mut StdRng::seed_from_u64(1));
+
+        let fauna = cell.fauna.as_ref().unwrap();
+        assert!(fauna.count(SpeciesId::CARNIVORE) < 10, "No carn was eaten");
+        assert_eq!(fauna.count(SpeciesId::APEX_PREDATOR), 3);
+        assert_eq!(cell.fodder, 800.0);
+    }
+
+    #[test]
+    fn test_spread_disease() {
+        let registry = SpeciesRegistry::default();
+        let disease = BTreeMap::from([(
+            SpeciesId::HERBIVORE,
+            DiseaseParams {
+                transmission: 0.2,
+                recovery: 0.0,
+                weight_loss: 0.5,
+                mortality: 0.0,
+            },
+        )]);
+
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::from(5, 20.0), 50, &registry)
+            .unwrap();
+        cell.spread_disease(&disease, &mut StdRng::seed_from_u64(1));
+        assert_eq!(cell.infection_counts(SpeciesId::HERBIVORE).susceptible, 50);
+
+        let mut sick = Stats::from(5, 20.0);
+        sick.infection = Infection::Infected;
+        cell.add_animals("Herbivore", sick, 5, &registry).unwrap();
+        cell.add_animals("Carnivore", Stats::from(5, 20.0), 5, &registry)
+            .unwrap();
+        cell.spread_disease(&disease, &mut StdRng::seed_from_u64(1));
+
+        let counts = cell.infection_counts(SpeciesId::HERBIVORE);
+        assert!(counts.infected > 5, "{counts:?}");
+        assert_eq!(counts.total(), 55);
+        assert_eq!(cell.infection_counts(SpeciesId::CARNIVORE).susceptible, 5);
+
+        // only the animals that were infected at the start lose weight
+        let weights: Vec<f32> = cell
+            .fauna
+            .as_ref()
+            .unwrap()
+            .get(SpeciesId::HERBIVORE)
+            .iter()
+            .map(|animal| animal.stats.weight)
+            .collect();
+        assert_eq!(weights.iter().filter(|&&weight| weight == 10.0).count(), 5);
+    }
+
+    #[test]
+    fn test_disease_adds_to_natural_mortality() {
+        let registry = SpeciesRegistry::default();
+        let disease = BTreeMap::from([(
+            SpeciesId::HERBIVORE,
+            DiseaseParams {
+                transmission: 0.0,
+                recovery: 0.0,
+                weight_loss: 0.0,
+                mortality: 0.2,
+            },
+        )]);
+
+        let mut cell = lowland((1, 1));
+        let mut sick = Stats::from(5, 20.0);
+        sick.infection = Infection::Infected;
+        cell.add_animals("Herbivore", sick, 500, &registry).unwrap();
+        cell.add_animals("Herbivore", Stats::from(5, 20.0), 500, &registry)
+            .unwrap();
+
+        let mut rng = StdRng::seed_from_u64(1);
+        cell.spread_disease(&disease, &mut rng);
+        cell.animal_death(&mut rng);
+
+        let counts = cell.infection_counts(SpeciesId::HERBIVORE);
+        let (healthy_deaths, sick_deaths) = (500 - counts.susceptible, 500 - counts.infected);
+        assert!(healthy_deaths > 0, "no natural deaths");
+        assert!(
+            sick_deaths > healthy_deaths + 50,
+            "{sick_deaths} infected vs {healthy_deaths} healthy died"
+        );
+    }
+
+    fn fertile_herbivores(cell: &mut Cell, sex: Sex, amount: u32) {
+        let params = HERBIVORE
+            .update(&[("gamma".to_string(), 1.0)].into())
+            .unwrap();
+        for _ in 0..amount {
+            let mut stats = Stats::from(5, 60.0);
+            stats.sex = sex;
+            cell.add_animal(Animal::new(SpeciesId::HERBIVORE, stats, params));
+        }
+    }
+
+    #[test]
+    fn test_sexual_births_need_a_male() {
+        let mut cell = lowland((1, 1));
+        fertile_herbivores(&mut cell, Sex::Female, 20);
+
+        cell.add_newborns(None, Reproduction::Sexual, &mut StdRng::seed_from_u64(1));
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE), 20);
+
+        cell.add_newborns(None, Reproduction::Asexual, &mut StdRng::seed_from_u64(1));
+        assert!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE) > 20);
+    }
+
+    #[test]
+    fn test_one_birth_per_male() {
+        let mut cell = lowland((1, 1));
+        fertile_herbivores(&mut cell, Sex::Female, 20);
+        fertile_herbivores(&mut cell, Sex::Male, 2);
+
+        cell.add_newborns(None, Reproduction::Sexual, &mut StdRng::seed_from_u64(1));
+
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE), 24);
+    }
+
+    #[test]
+    fn test_added_animals_split_sexes() {
+        let registry = SpeciesRegistry::default();
+        let mut cell = lowland((1, 1));
+        cell.add_animal_from("Herbivore".to_string(), 5, &registry)
+            .unwrap();
+        cell.add_animals("Carnivore", Stats::from(5, 20.0), 4, &registry)
+            .unwrap();
+
+        let males = |species| {
+            let animals = cell.fauna.as_ref().unwrap().get(species);
+            animals.iter().filter(|a| a.stats.sex == Sex::Male).count()
+        };
+        assert_eq!(males(SpeciesId::HERBIVORE), 2);
+        assert_eq!(males(SpeciesId::CARNIVORE), 2);
+    }
+
+    #[test]
+    fn test_sexual_births_from_added_animals() {
+        let registry = SpeciesRegistry::default();
+        let mut cell = lowland((1, 1));
+        cell.add_animals("Herbivore", Stats::from(5, 60.0), 100, &registry)
+            .unwrap();
+
+        cell.add_newborns(None, Reproduction::Sexual, &mut StdRng::seed_from_u64(1));
+
+        assert!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE) > 100);
+    }
 }
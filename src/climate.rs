@@ -0,0 +1,176 @@
+use crate::cell::{Cell, CellType};
+use crate::error::BioSimError;
+use serde::Deserialize;
+
+// a scheduled change of the environment, in a scenario file:
+//
+// [[climate]]
+// name = "drought"
+// years = [40, 55]            first year it is active and the year it ends
+// cell_types = ["Lowland"]    all cell types when left out
+// region = [[1, 1], [10, 5]]  corners of a rectangle of cells, the whole island when left out
+// f_max = 0.3                 factor on f_max
+// omega = 1.5                 factor on the mortality parameter omega
+//
+// Years count the yearly cycles, the event above is active in the cycles that
+// are logged as years 40 to 54. Overlapping events multiply their factors.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClimateEvent {
+    pub name: String,
+    pub years: (u32, u32),
+    pub cell_types: Option<Vec<CellType>>,
+    pub region: Option<Region>,
+    #[serde(default = "no_change")]
+    pub f_max: f32,
+    #[serde(default = "no_change")]
+    pub omega: f32,
+}
+
+fn no_change() -> f32 {
+    1.0
+}
+
+impl ClimateEvent {
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        let invalid = |key, value, reason| BioSimError::InvalidParameter {
+            key: format!("{key} of climate event {}", self.name),
+            value,
+            reason,
+        };
+
+        for (key, factor) in [("f_max", self.f_max), ("omega", self.omega)] {
+            if !factor.is_finite() || factor < 0.0 {
+                return Err(invalid(key, factor, "must be a non-negative number"));
+            }
+        }
+        if self.years.0 >= self.years.1 {
+            return Err(invalid(
+                "end year",
+                self.years.1 as f32,
+                "must come after the first year",
+            ));
+        }
+        if let Some(region) = self.region {
+            validate_region(region, &format!("climate event {}", self.name))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_active(&self, year: u32) -> bool {
+        (self.years.0..self.years.1).contains(&year)
+    }
+
+    pub fn applies_to(&self, cell: &Cell) -> bool {
+        let in_types = self
+            .cell_types
+            .as_ref()
+            .is_none_or(|types| types.contains(&cell.get_cell()));
+        let in_region = self
+            .region
+            .is_none_or(|region| region_contains(region, cell.loc));
+
+        in_types && in_region
+    }
+}
+
+// a rectangle of cells given by its top left and bottom right corner, inclusive
+pub type Region = ((u32, u32), (u32, u32));
+
+pub fn region_contains(((x0, y0), (x1, y1)): Region, (x, y): (u32, u32)) -> bool {
+    (x0..=x1).contains(&x) && (y0..=y1).contains(&y)
+}
+
+pub fn validate_region(region: Region, name: &str) -> Result<(), BioSimError> {
+    let ((x0, y0), (x1, y1)) = region;
+    if x0 > x1 || y0 > y1 {
+        return Err(BioSimError::Scenario(format!(
+            "Region of {name} must go from the top left to the bottom right corner"
+        )));
+    }
+    Ok(())
+}
+
+// the combined effect of the events active on a cell this year
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClimateFactors {
+    pub f_max: f32,
+    pub omega: f32,
+}
+
+impl Default for ClimateFactors {
+    fn default() -> Self {
+        ClimateFactors {
+            f_max: 1.0,
+            omega: 1.0,
+        }
+    }
+}
+
+impl ClimateFactors {
+    pub fn apply(&mut self, event: &ClimateEvent) {
+        self.f_max *= event.f_max;
+        self.omega *= event.omega;
+    }
+}
+
+#[cfg(test)]
+mod climate_tests {
+    use super::*;
+    use crate::cell::LandscapeParameters;
+
+    fn drought() -> ClimateEvent {
+        ClimateEvent {
+            name: "drought".to_string(),
+            years: (40, 55),
+            cell_types: Some(vec![CellType::Lowland]),
+            region: None,
+            f_max: 0.3,
+            omega: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_active_years() {
+        let event = drought();
+        assert!(!event.is_active(39));
+        assert!(event.is_active(40));
+        assert!(event.is_active(54));
+        assert!(!event.is_active(55));
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let landscape = LandscapeParameters::default();
+        let lowland = Cell::new(CellType::Lowland, (3, 2), &landscape);
+        let highland = Cell::new(CellType::Highland, (3, 2), &landscape);
+
+        let mut event = drought();
+        assert!(event.applies_to(&lowland));
+        assert!(!event.applies_to(&highland));
+
+        event.cell_types = None;
+        event.region = Some(((1, 1), (2, 5)));
+        assert!(!event.applies_to(&lowland));
+        event.region = Some(((1, 1), (3, 2)));
+        assert!(event.applies_to(&highland));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(drought().validate().is_ok());
+
+        let mut event = drought();
+        event.years = (55, 40);
+        assert!(event.validate().is_err());
+
+        let mut event = drought();
+        event.f_max = -0.5;
+        assert!(event.validate().is_err());
+
+        let mut event = drought();
+        event.region = Some(((4, 1), (2, 5)));
+        assert!(event.validate().is_err());
+    }
+}
@@ -0,0 +1,573 @@
+use crate::animal::SpeciesRegistry;
+pub use crate::animal::{Genetics, Reproduction, SpeciesEntry};
+pub use crate::cell::Regrowth;
+use crate::cell::{CellType, LandscapeParameters};
+pub use crate::climate::ClimateEvent;
+pub use crate::disease::DiseaseParams;
+use crate::error::BioSimError;
+pub use crate::events::Event;
+pub use crate::island::{AnimalEntry, PopulationEntry};
+pub use crate::migration::{Kernel, MigrationMode, Movement, Neighbourhood};
+pub use crate::terrain::TerrainDynamics;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+// example of a scenario file in toml:
+//
+// seed = 12345
+// num_years = 100
+// reproduction = "sexual"
+// island_map = """
+// WWWW
+// WLHW
+// WWWW"""
+//
+// [[ini_pop]]
+// loc = [1, 1]
+// pop = [
+//     { species = "Herbivore", count = 50 },
+//     { species = "Carnivore", age = 5, weight = 20.0, sex = "Female" },
+// ]
+//
+// [animal_params.Carnivore]
+// f = 30.0
+//
+// [[species]]
+// name = "Fox"
+// display_name = "foxes"
+// based_on = "Carnivore"
+// prey = ["Herbivore"]
+// params = { f = 20.0 }
+//
+// [genetics.mutation]
+// mu = 0.02
+// f = 1.0
+//
+// [landscape_params]
+// Lowland = 600.0
+//
+// [regrowth]
+// Lowland = { model = "logistic", alpha = 0.5 }
+// Highland = { model = "seasonal", alpha = 0.3, die_off = 0.1, steps = 12 }
+//
+// [disease.Herbivore]
+// transmission = 0.02
+// recovery = 0.3
+// mortality = 0.05
+//
+// [migration]
+// Herbivore = "guided"
+//
+// [movement.Carnivore]
+// dispersal = { kernel = "levy", exponent = 2.0 }
+//
+// [terrain]
+// degrade_after = 3
+// recover_after = 5
+//
+// [[climate]]
+// name = "drought"
+// years = [40, 55]
+// cell_types = ["Lowland"]
+// f_max = 0.3
+//
+// [[events]]
+// kind = "fire"
+// year = 10
+// region = [[1, 1], [2, 1]]
+// kill = 0.5
+//
+// [output]
+// print_interval = 10
+// dir = "results"
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub island_map: String,
+    // surround the map with water if its border is not all water,
+    // population locations still refer to the map as written
+    #[serde(default)]
+    pub pad_map: bool,
+    pub ini_pop: Vec<PopulationEntry>,
+    #[serde(default)]
+    pub seed: u64,
+    pub num_years: u32,
+    // species added to the built-in ones, in the order they are registered
+    #[serde(default)]
+    pub species: Vec<SpeciesEntry>,
+    #[serde(default)]
+    pub animal_params: HashMap<String, HashMap<String, f32>>,
+    // per species, the animals are susceptible unless ini_pop says otherwise
+    #[serde(default)]
+    pub disease: HashMap<String, DiseaseParams>,
+    // per species, "random" or "guided", a random walk unless given
+    #[serde(default)]
+    pub migration: HashMap<String, MigrationMode>,
+    // per species neighbourhood and dispersal kernel, see Movement
+    #[serde(default)]
+    pub movement: HashMap<String, Movement>,
+    // heritable parameters, off unless given
+    #[serde(default)]
+    pub genetics: Option<Genetics>,
+    // "asexual" unless given
+    #[serde(default)]
+    pub reproduction: Reproduction,
+    #[serde(default)]
+    pub landscape_params: HashMap<CellType, f32>,
+    // fodder is reset to f_max every year for cell types not listed
+    #[serde(default)]
+    pub regrowth: HashMap<CellType, Regrowth>,
+    // overgrazed cells degrade and rested ones recover, off unless given
+    #[serde(default)]
+    pub terrain: Option<TerrainDynamics>,
+    // scheduled droughts and other changes of the environment
+    #[serde(default)]
+    pub climate: Vec<ClimateEvent>,
+    // fires, floods and culls, see Event
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub output: OutputConfig,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    // print the population to stdout every print_interval years
+    pub print_interval: Option<u32>,
+    // directory for the yearly csv population logs, created if missing
+    pub dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn build(
+        island_map: String,
+        ini_pop: Vec<((u32, u32), String, u32)>,
+    ) -> Result<Config, &'static str> {
+        let ini_pop = ini_pop.into_iter().map(PopulationEntry::from).collect();
+
+        Ok(Config {
+            island_map,
+            pad_map: false,
+            ini_pop,
+            seed: 0,
+            num_years: 0,
+            species: Vec::new(),
+            animal_params: HashMap::new(),
+            disease: HashMap::new(),
+            migration: HashMap::new(),
+            movement: HashMap::new(),
+            genetics: None,
+            reproduction: Reproduction::default(),
+            landscape_params: HashMap::new(),
+            regrowth: HashMap::new(),
+            terrain: None,
+            climate: Vec::new(),
+            events: Vec::new(),
+            output: OutputConfig::default(),
+        })
+    }
+
+    // reads a .toml or .json scenario file
+    pub fn from_file(path: &Path) -> Result<Config, BioSimError> {
+        let contents = fs::read_to_string(path).map_err(|err| {
+            BioSimError::Scenario(format!("Could not read {}: {err}", path.display()))
+        })?;
+
+        let config = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Config::from_toml_str(&contents),
+            Some("json") => Config::from_json_str(&contents),
+            _ => Err(BioSimError::Scenario(
+                "Scenario file must end in .toml or .json".to_string(),
+            )),
+        };
+
+        config.map_err(|err| BioSimError::Scenario(format!("{}: {err}", path.display())))
+    }
+
+    pub fn from_toml_str(contents: &str) -> Result<Config, BioSimError> {
+        let config: Config =
+            toml::from_str(contents).map_err(|err| BioSimError::Scenario(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Config, BioSimError> {
+        let config: Config =
+            serde_json::from_str(contents).map_err(|err| BioSimError::Scenario(err.to_string()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    // checks the values serde cannot, naming the key at fault
+    fn validate(&self) -> Result<(), BioSimError> {
+        let mut registry = SpeciesRegistry::default();
+        for entry in &self.species {
+            registry
+                .register_entry(entry)
+                .map_err(|err| BioSimError::Scenario(format!("species.{}: {err}", entry.name)))?;
+        }
+
+        for (name, partial_params) in &self.animal_params {
+            registry
+                .id(name)
+                .and_then(|species| registry.set(species, partial_params))
+                .map_err(|err| BioSimError::Scenario(format!("animal_params.{name}: {err}")))?;
+        }
+
+        for (name, disease) in &self.disease {
+            registry
+                .id(name)
+                .and_then(|_| disease.validate())
+                .map_err(|err| BioSimError::Scenario(format!("disease.{name}: {err}")))?;
+        }
+
+        for name in self.migration.keys() {
+            registry
+                .id(name)
+                .map_err(|err| BioSimError::Scenario(format!("migration.{name}: {err}")))?;
+        }
+
+        for (name, movement) in &self.movement {
+            let mode = self
+                .migration
+                .get(name)
+                .copied()
+                .unwrap_or(MigrationMode::Random);
+            registry
+                .id(name)
+                .and_then(|_| movement.strategy(mode))
+                .map_err(|err| BioSimError::Scenario(format!("movement.{name}: {err}")))?;
+        }
+
+        if let Some(genetics) = &self.genetics {
+            genetics
+                .validate()
+                .map_err(|err| BioSimError::Scenario(format!("genetics: {err}")))?;
+        }
+
+        let mut landscape = LandscapeParameters::default();
+        for (&cell_type, &f_max) in &self.landscape_params {
+            landscape.set_f_max(cell_type, f_max).map_err(|err| {
+                BioSimError::Scenario(format!("landscape_params.{cell_type:?}: {err}"))
+            })?;
+        }
+
+        for (&cell_type, &regrowth) in &self.regrowth {
+            landscape
+                .set_regrowth(cell_type, regrowth)
+                .map_err(|err| BioSimError::Scenario(format!("regrowth.{cell_type:?}: {err}")))?;
+        }
+
+        if let Some(terrain) = &self.terrain {
+            terrain
+                .validate()
+                .map_err(|err| BioSimError::Scenario(format!("terrain: {err}")))?;
+        }
+
+        for event in &self.climate {
+            event
+                .validate()
+                .map_err(|err| BioSimError::Scenario(format!("climate.{}: {err}", event.name)))?;
+        }
+
+        for (number, event) in self.events.iter().enumerate() {
+            let species = match event {
+                Event::Cull { species, .. } => registry.id(species).map(|_| ()),
+                _ => Ok(()),
+            };
+            species.and_then(|_| event.validate()).map_err(|err| {
+                BioSimError::Scenario(format!("events[{number}] ({}): {err}", event.kind()))
+            })?;
+        }
+
+        if self.output.print_interval == Some(0) {
+            return Err(BioSimError::Scenario(
+                "output.print_interval must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+seed = 7
+num_years = 20
+island_map = """
+WWWW
+WLHW
+WWWW"""
+
+[[ini_pop]]
+loc = [1, 1]
+pop = [
+    { species = "Herbivore", count = 40 },
+    { species = "Carnivore", age = 3, weight = 12.5 },
+]
+
+[animal_params.Carnivore]
+f = 30.0
+
+[landscape_params]
+Lowland = 600.0
+
+[output]
+print_interval = 5
+"#;
+
+    #[test]
+    fn parse_toml_scenario() {
+        let config = Config::from_toml_str(SCENARIO).unwrap();
+
+        assert_eq!(config.seed, 7);
+        assert_eq!(config.num_years, 20);
+        assert_eq!(config.ini_pop[0].loc, (1, 1));
+        assert_eq!(config.ini_pop[0].pop[0].count, 40);
+        assert_eq!(config.ini_pop[0].pop[1].count, 1);
+        assert_eq!(config.ini_pop[0].pop[1].weight, Some(12.5));
+        assert_eq!(config.animal_params["Carnivore"]["f"], 30.0);
+        assert_eq!(config.landscape_params[&CellType::Lowland], 600.0);
+        assert_eq!(config.output.print_interval, Some(5));
+    }
+
+    #[test]
+    fn parse_json_scenario() {
+        let json = r#"{
+            "island_map": "WWW\nWLW\nWWW",
+            "ini_pop": [{"loc": [1, 1], "pop": [{"species": "Herbivore", "count": 10}]}],
+            "num_years": 5
+        }"#;
+        let config = Config::from_json_str(json).unwrap();
+
+        assert_eq!(config.seed, 0);
+        assert_eq!(config.ini_pop[0].pop[0].species, "Herbivore");
+    }
+
+    #[test]
+    fn unknown_key_is_reported() {
+        let scenario = SCENARIO.replace("num_years = 20", "num_years = 20\nyears = 20");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+
+        assert!(err.contains("years"), "{err}");
+        assert!(err.contains("line"), "{err}");
+    }
+
+    #[test]
+    fn invalid_parameter_names_key() {
+        let scenario = SCENARIO.replace("f = 30.0", "eta = 3.0");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+
+        assert!(err.contains("animal_params.Carnivore"), "{err}");
+    }
+
+    #[test]
+    fn parse_species() {
+        let scenario = format!(
+            "{SCENARIO}\n[[species]]\nname = \"Fox\"\nbased_on = \"Carnivore\"\nprey = [\"Herbivore\"]\n\n[animal_params.Fox]\nf = 15.0\n"
+        );
+        let config = Config::from_toml_str(&scenario).unwrap();
+
+        assert_eq!(config.species[0].name, "Fox");
+        assert_eq!(config.species[0].prey, Some(vec!["Herbivore".to_string()]));
+        assert_eq!(config.animal_params["Fox"]["f"], 15.0);
+    }
+
+    #[test]
+    fn unknown_species_names_key() {
+        let scenario = format!("{SCENARIO}\n[animal_params.Fox]\nf = 15.0\n");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("animal_params.Fox"), "{err}");
+
+        let scenario = format!(
+            "{SCENARIO}\n[[species]]\nname = \"Owl\"\nbased_on = \"Carnivore\"\nprey = [\"Mouse\"]\n"
+        );
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("species.Owl"), "{err}");
+        assert!(err.contains("Mouse"), "{err}");
+    }
+
+    #[test]
+    fn parse_genetics() {
+        let config = Config::from_toml_str(SCENARIO).unwrap();
+        assert_eq!(config.genetics, None);
+
+        let scenario = format!("{SCENARIO}\n[genetics.mutation]\nmu = 0.02\nf = 1.0\n");
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(config.genetics.unwrap().mutation["f"], 1.0);
+
+        let scenario = format!("{SCENARIO}\n[genetics.mutation]\nspeed = 0.02\n");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("genetics"), "{err}");
+    }
+
+    #[test]
+    fn parse_reproduction() {
+        let config = Config::from_toml_str(SCENARIO).unwrap();
+        assert_eq!(config.reproduction, Reproduction::Asexual);
+
+        let scenario = SCENARIO
+            .replace("seed = 7", "seed = 7\nreproduction = \"sexual\"")
+            .replace("weight = 12.5", "weight = 12.5, sex = \"Male\"");
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(config.reproduction, Reproduction::Sexual);
+        assert_eq!(config.ini_pop[0].pop[1].sex, Some(crate::animal::Sex::Male));
+    }
+
+    #[test]
+    fn parse_regrowth() {
+        let scenario = format!(
+            "{SCENARIO}\n[regrowth]\nLowland = {{ model = \"logistic\", alpha = 0.5 }}\nHighland = {{ model = \"seasonal\", alpha = 0.3, die_off = 0.1, steps = 12 }}\n"
+        );
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(
+            config.regrowth[&CellType::Lowland],
+            Regrowth::Logistic {
+                alpha: 0.5,
+                f_min: 0.0
+            }
+        );
+        assert!(matches!(
+            config.regrowth[&CellType::Highland],
+            Regrowth::Seasonal { steps: 12, .. }
+        ));
+
+        let scenario =
+            format!("{SCENARIO}\n[regrowth]\nLowland = {{ model = \"logistic\", alpha = -1.0 }}\n");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("regrowth.Lowland"), "{err}");
+
+        let scenario =
+            format!("{SCENARIO}\n[regrowth]\nLowland = {{ model = \"logistic\", rate = 0.5 }}\n");
+        assert!(Config::from_toml_str(&scenario).is_err());
+    }
+
+    #[test]
+    fn parse_climate() {
+        let scenario = format!(
+            "{SCENARIO}\n[[climate]]\nname = \"drought\"\nyears = [40, 55]\ncell_types = [\"Lowland\"]\nregion = [[1, 1], [2, 1]]\nf_max = 0.3\n"
+        );
+        let config = Config::from_toml_str(&scenario).unwrap();
+        let event = &config.climate[0];
+        assert_eq!(event.years, (40, 55));
+        assert_eq!(event.region, Some(((1, 1), (2, 1))));
+        assert_eq!((event.f_max, event.omega), (0.3, 1.0));
+
+        let scenario = scenario.replace("years = [40, 55]", "years = [55, 40]");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("climate.drought"), "{err}");
+    }
+
+    #[test]
+    fn parse_disease() {
+        let scenario = SCENARIO.replace(
+            "{ species = \"Herbivore\", count = 40 }",
+            "{ species = \"Herbivore\", count = 40, infection = \"Infected\" }",
+        );
+        let scenario =
+            format!("{scenario}\n[disease.Herbivore]\ntransmission = 0.02\nrecovery = 0.3\n");
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(config.disease["Herbivore"].transmission, 0.02);
+        assert_eq!(config.disease["Herbivore"].mortality, 0.0);
+        assert_eq!(
+            config.ini_pop[0].pop[0].infection,
+            Some(crate::disease::Infection::Infected)
+        );
+
+        let scenario = format!("{SCENARIO}\n[disease.Fox]\ntransmission = 0.02\nrecovery = 0.3\n");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("disease.Fox"), "{err}");
+
+        let scenario =
+            format!("{SCENARIO}\n[disease.Herbivore]\ntransmission = 2.0\nrecovery = 0.3\n");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("transmission"), "{err}");
+    }
+
+    #[test]
+    fn parse_migration() {
+        let scenario = format!("{SCENARIO}\n[migration]\nHerbivore = \"guided\"\n");
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(config.migration["Herbivore"], MigrationMode::Guided);
+
+        let err = Config::from_toml_str(&scenario.replace("Herbivore =", "Wolf ="))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("migration.Wolf"), "{err}");
+
+        let scenario = scenario.replace("\"guided\"", "\"teleport\"");
+        assert!(Config::from_toml_str(&scenario).is_err());
+    }
+
+    #[test]
+    fn parse_movement() {
+        let scenario = format!(
+            "{SCENARIO}\n[movement.Carnivore]\ndispersal = {{ kernel = \"exponential\", mean = 3.0 }}\n"
+        );
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(
+            config.movement["Carnivore"],
+            Movement {
+                neighbourhood: Neighbourhood::VonNeumann,
+                dispersal: Some(Kernel::Exponential { mean: 3.0 }),
+            }
+        );
+
+        let guided = format!("{scenario}\n[migration]\nCarnivore = \"guided\"\n");
+        let err = Config::from_toml_str(&guided).unwrap_err().to_string();
+        assert!(err.contains("movement.Carnivore"), "{err}");
+
+        let with_radius =
+            scenario.replace("dispersal", "neighbourhood = { radius = 2 }\ndispersal");
+        let err = Config::from_toml_str(&with_radius).unwrap_err().to_string();
+        assert!(err.contains("neighbourhood"), "{err}");
+
+        let scenario =
+            format!("{SCENARIO}\n[movement.Herbivore]\nneighbourhood = {{ radius = 2 }}\n");
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(
+            config.movement["Herbivore"].neighbourhood,
+            Neighbourhood::Radius(2)
+        );
+        assert_eq!(config.movement["Herbivore"].dispersal, None);
+    }
+
+    #[test]
+    fn parse_events() {
+        let scenario = format!(
+            "{SCENARIO}\n[[events]]\nkind = \"flood\"\nyear = 5\nregion = [[1, 1], [1, 1]]\n\n[[events]]\nkind = \"cull\"\nyear = 8\nspecies = \"Carnivore\"\ncount = 3\n"
+        );
+        let config = Config::from_toml_str(&scenario).unwrap();
+        assert_eq!(config.events.len(), 2);
+        assert_eq!(config.events[1].kind(), "cull");
+
+        let err =
+            Config::from_toml_str(&scenario.replace("\"Carnivore\"\ncount", "\"Wolf\"\ncount"))
+                .unwrap_err()
+                .to_string();
+        assert!(err.contains("events[1] (cull)"), "{err}");
+        assert!(err.contains("Wolf"), "{err}");
+    }
+
+    #[test]
+    fn parse_terrain() {
+        let config = Config::from_toml_str(SCENARIO).unwrap();
+        assert_eq!(config.terrain, None);
+
+        let scenario = format!("{SCENARIO}\n[terrain]\ndegrade_after = 3\nrecover_after = 5\n");
+        let terrain = Config::from_toml_str(&scenario).unwrap().terrain.unwrap();
+        assert_eq!((terrain.degrade_after, terrain.exhausted), (3, 0.05));
+
+        let scenario = scenario.replace("degrade_after = 3", "degrade_after = 0");
+        let err = Config::from_toml_str(&scenario).unwrap_err().to_string();
+        assert!(err.contains("terrain: "), "{err}");
+    }
+}
@@ -0,0 +1,113 @@
+use crate::error::BioSimError;
+use serde::Deserialize;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize)]
+pub enum Infection {
+    #[default]
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+// an infectious disease of one species, in a scenario file:
+//
+// [disease.Herbivore]
+// transmission = 0.02  chance of catching it from each infected animal of the cell
+// recovery = 0.3       yearly chance of an infected animal to recover for good
+// weight_loss = 0.1    share of the weight an infected animal loses per year
+// mortality = 0.05     yearly chance of an infected animal to die of it, on top of
+//                      the natural mortality all animals face
+//
+// A susceptible animal in a cell with n infected animals of its species is
+// infected with probability 1 - (1 - transmission)^n.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiseaseParams {
+    pub transmission: f32,
+    pub recovery: f32,
+    #[serde(default)]
+    pub weight_loss: f32,
+    #[serde(default)]
+    pub mortality: f32,
+}
+
+impl DiseaseParams {
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        let probabilities = [
+            ("transmission", self.transmission),
+            ("recovery", self.recovery),
+            ("weight_loss", self.weight_loss),
+            ("mortality", self.mortality),
+        ];
+
+        for (key, value) in probabilities {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(BioSimError::InvalidParameter {
+                    key: key.to_string(),
+                    value,
+                    reason: "must be between 0 and 1",
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn infection_probability(&self, infected: usize) -> f32 {
+        1.0 - (1.0 - self.transmission).powi(infected as i32)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InfectionCounts {
+    pub susceptible: usize,
+    pub infected: usize,
+    pub recovered: usize,
+}
+
+impl InfectionCounts {
+    pub fn add(&mut self, infection: Infection) {
+        match infection {
+            Infection::Susceptible => self.susceptible += 1,
+            Infection::Infected => self.infected += 1,
+            Infection::Recovered => self.recovered += 1,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.susceptible + self.infected + self.recovered
+    }
+}
+
+#[cfg(test)]
+mod disease_tests {
+    use super::*;
+
+    #[test]
+    fn test_infection_probability() {
+        let disease = DiseaseParams {
+            transmission: 0.5,
+            recovery: 0.0,
+            weight_loss: 0.0,
+            mortality: 0.0,
+        };
+
+        assert_eq!(disease.infection_probability(0), 0.0);
+        assert_eq!(disease.infection_probability(1), 0.5);
+        assert_eq!(disease.infection_probability(2), 0.75);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut disease = DiseaseParams {
+            transmission: 0.1,
+            recovery: 0.2,
+            weight_loss: 0.0,
+            mortality: 0.0,
+        };
+        assert!(disease.validate().is_ok());
+
+        disease.mortality = 1.5;
+        assert!(disease.validate().is_err());
+    }
+}
@@ -0,0 +1,86 @@
+use std::{error::Error, fmt, io};
+
+// lines and columns are 1-based and count only the non-empty lines of a map
+#[derive(Debug)]
+pub enum BioSimError {
+    EmptyMap,
+    RaggedMap {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCell {
+        line: usize,
+        col: usize,
+        ch: char,
+    },
+    // locations on the outer ring of the map that are not water
+    NonWaterBorder(Vec<(u32, u32)>),
+    UnknownSpecies(String),
+    DuplicateSpecies(String),
+    AnimalsInWater((u32, u32)),
+    OutOfBounds((u32, u32)),
+    UnknownParameter(String),
+    InvalidParameter {
+        key: String,
+        value: f32,
+        reason: &'static str,
+    },
+    Scenario(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for BioSimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BioSimError::EmptyMap => write!(f, "Island map is empty"),
+            BioSimError::RaggedMap {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Map line {line} has {found} cells, expected {expected} like the first line"
+            ),
+            BioSimError::InvalidCell { line, col, ch } => {
+                write!(f, "Invalid cell '{ch}' at map line {line}, column {col}")
+            }
+            BioSimError::NonWaterBorder(positions) => {
+                let positions: Vec<String> =
+                    positions.iter().map(|loc| format!("{loc:?}")).collect();
+                write!(
+                    f,
+                    "Map must be surrounded by water, found land at {}",
+                    positions.join(", ")
+                )
+            }
+            BioSimError::UnknownSpecies(name) => write!(f, "Unknown species: {name}"),
+            BioSimError::DuplicateSpecies(name) => write!(f, "Species {name} already exists"),
+            BioSimError::AnimalsInWater(loc) => {
+                write!(f, "Cannot place animals in water at {loc:?}")
+            }
+            BioSimError::OutOfBounds(loc) => write!(f, "No cell at location {loc:?}"),
+            BioSimError::UnknownParameter(key) => write!(f, "Unknown parameter: {key}"),
+            BioSimError::InvalidParameter { key, value, reason } => {
+                write!(f, "Parameter {key} {reason}, got {value}")
+            }
+            BioSimError::Scenario(msg) => write!(f, "{msg}"),
+            BioSimError::Io(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for BioSimError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            BioSimError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for BioSimError {
+    fn from(err: io::Error) -> Self {
+        BioSimError::Io(err)
+    }
+}
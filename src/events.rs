@@ -0,0 +1,205 @@
+use crate::climate::{validate_region, Region};
+use crate::error::BioSimError;
+use serde::Deserialize;
+
+// a shock scripted for one year, applied before the normal yearly phases:
+//
+// [[events]]
+// kind = "fire"                 fodder burns down and each animal dies with probability kill
+// year = 10
+// region = [[1, 1], [4, 3]]
+// kill = 0.5
+//
+// [[events]]
+// kind = "flood"                lowland turns into water for duration years, each animal
+// year = 20                     drowns with probability drown, the others flee to a
+// region = [[2, 2], [3, 3]]     random neighbouring land cell or drown if there is none
+// duration = 2
+// drown = 0.3
+//
+// [[events]]
+// kind = "cull"                 removes the count fittest animals of the species,
+// year = 30                     in the region or on the whole island
+// species = "Carnivore"
+// count = 20
+//
+// Years are counted like the logged years, events of the same year are applied in order.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Event {
+    Fire {
+        year: u32,
+        region: Region,
+        #[serde(default)]
+        kill: f32,
+    },
+    Flood {
+        year: u32,
+        region: Region,
+        #[serde(default = "one_year")]
+        duration: u32,
+        #[serde(default)]
+        drown: f32,
+    },
+    Cull {
+        year: u32,
+        species: String,
+        count: u32,
+        region: Option<Region>,
+    },
+}
+
+fn one_year() -> u32 {
+    1
+}
+
+impl Event {
+    pub fn year(&self) -> u32 {
+        match *self {
+            Event::Fire { year, .. } | Event::Flood { year, .. } | Event::Cull { year, .. } => year,
+        }
+    }
+
+    pub fn region_mut(&mut self) -> Option<&mut Region> {
+        match self {
+            Event::Fire { region, .. } | Event::Flood { region, .. } => Some(region),
+            Event::Cull { region, .. } => region.as_mut(),
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Fire { .. } => "fire",
+            Event::Flood { .. } => "flood",
+            Event::Cull { .. } => "cull",
+        }
+    }
+
+    // the species of a cull is checked when the schedule is given to the island
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        let probability = |key: &str, value: f32| {
+            if (0.0..=1.0).contains(&value) {
+                Ok(())
+            } else {
+                Err(BioSimError::InvalidParameter {
+                    key: format!("{key} of {}", self.kind()),
+                    value,
+                    reason: "must be between 0 and 1",
+                })
+            }
+        };
+
+        match *self {
+            Event::Fire { region, kill, .. } => {
+                validate_region(region, self.kind())?;
+                probability("kill", kill)
+            }
+            Event::Flood {
+                region,
+                duration,
+                drown,
+                ..
+            } => {
+                validate_region(region, self.kind())?;
+                if duration == 0 {
+                    return Err(BioSimError::InvalidParameter {
+                        key: "duration of flood".to_string(),
+                        value: 0.0,
+                        reason: "must be at least 1 year",
+                    });
+                }
+                probability("drown", drown)
+            }
+            Event::Cull { region, .. } => match region {
+                Some(region) => validate_region(region, self.kind()),
+                None => Ok(()),
+            },
+        }
+    }
+}
+
+// what an applied event did, kind is "fire", "flood", "cull" or "flood receded"
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub year: u32,
+    pub kind: &'static str,
+    pub cells: usize,
+    pub killed: usize,
+    pub moved: usize,
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Schedule {
+        events: Vec<Event>,
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let schedule: Schedule = toml::from_str(
+            r#"
+            [[events]]
+            kind = "fire"
+            year = 10
+            region = [[1, 1], [4, 3]]
+            kill = 0.5
+
+            [[events]]
+            kind = "flood"
+            year = 20
+            region = [[2, 2], [3, 3]]
+
+            [[events]]
+            kind = "cull"
+            year = 30
+            species = "Carnivore"
+            count = 20
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            schedule.events[0],
+            Event::Fire {
+                year: 10,
+                region: ((1, 1), (4, 3)),
+                kill: 0.5
+            }
+        );
+        assert!(matches!(
+            schedule.events[1],
+            Event::Flood { duration: 1, .. }
+        ));
+        assert_eq!(schedule.events[2].year(), 30);
+        assert_eq!(schedule.events[2].kind(), "cull");
+    }
+
+    #[test]
+    fn test_validate() {
+        let fire = Event::Fire {
+            year: 1,
+            region: ((1, 1), (2, 2)),
+            kill: 1.5,
+        };
+        assert!(fire.validate().is_err());
+
+        let flood = Event::Flood {
+            year: 1,
+            region: ((1, 1), (2, 2)),
+            duration: 0,
+            drown: 0.0,
+        };
+        assert!(flood.validate().is_err());
+
+        let cull = Event::Cull {
+            year: 1,
+            species: "Carnivore".to_string(),
+            count: 5,
+            region: Some(((3, 1), (2, 2))),
+        };
+        assert!(cull.validate().is_err());
+    }
+}
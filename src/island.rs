@@ -1,9 +1,19 @@
-use crate::animal::{AnimalTrait, Carnivore, Herbivore};
-use crate::cell::{Cell, CellType, Fauna};
+use crate::animal::{
+    Animal, AnimalTrait, Genetics, Reproduction, Sex, SpeciesEntry, SpeciesId, SpeciesRegistry,
+    Stats,
+};
+use crate::cell::{Cell, CellType, LandscapeParameters, Regrowth};
+use crate::climate::{region_contains, ClimateEvent, ClimateFactors, Region};
+use crate::disease::{DiseaseParams, Infection, InfectionCounts};
+use crate::error::BioSimError;
+use crate::events::{Event, EventRecord};
+use crate::migration::{MigrationStrategies, MigrationStrategy, Surroundings};
+use crate::terrain::TerrainDynamics;
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
+use serde::Deserialize;
 use std::{
-    collections::HashMap,
-    error::Error,
-    iter::{Map, MapWhile},
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
 };
 
 mod island_params {
@@ -17,245 +27,952 @@ mod island_params {
 }
 
 use island_params::ISLAND;
-type MapHashmap<'a> = HashMap<(u32, u32), Cell>;
-#[derive(Debug)]
-pub struct Island<'a> {
-    raw_str: String,
-    map_vec: Vec<&'a str>,
-    height: usize,
-    width: usize,
 
-    pub map: MapHashmap<'a>,
+// a group of animals placed in one cell, the population input format:
+// PopulationEntry { loc: (1, 1), pop: vec![AnimalEntry { species: "Herbivore".to_string(), age: Some(5), weight: Some(20.0), sex: None, infection: None, count: 1 }] }
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PopulationEntry {
+    pub loc: (u32, u32),
+    pub pop: Vec<AnimalEntry>,
+}
+
+// age and weight fall back to the defaults of Stats::new_default, animals are
+// susceptible unless an infection is given, and without a sex they are split
+// evenly between females and males
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnimalEntry {
+    pub species: String,
+    pub age: Option<u32>,
+    pub weight: Option<f32>,
+    pub sex: Option<Sex>,
+    pub infection: Option<Infection>,
+    #[serde(default = "default_count")]
+    pub count: u32,
+}
+
+fn default_count() -> u32 {
+    1
+}
+
+impl AnimalEntry {
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::new_default();
+        if let Some(age) = self.age {
+            stats.age = age;
+        }
+        if let Some(weight) = self.weight {
+            stats.weight = weight;
+        }
+        if let Some(sex) = self.sex {
+            stats.sex = sex;
+        }
+        if let Some(infection) = self.infection {
+            stats.infection = infection;
+        }
+        stats
+    }
+}
+
+// example of tuple: ((1, 1), "Herbivore".to_string(), 200)
+impl From<((u32, u32), String, u32)> for PopulationEntry {
+    fn from((loc, species, count): ((u32, u32), String, u32)) -> Self {
+        PopulationEntry {
+            loc,
+            pop: vec![AnimalEntry {
+                species,
+                age: None,
+                weight: None,
+                sex: None,
+                infection: None,
+                count,
+            }],
+        }
+    }
+}
+// mean and variance of a heritable parameter over all animals of a species
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitStats {
+    pub species: SpeciesId,
+    pub key: String,
+    pub mean: f32,
+    pub variance: f32,
+}
 
-    pub moving_herb_vec: Vec<(&'a Herbivore, (u32, u32), (u32, u32))>,
-    pub moving_carn_vec: Vec<(&'a Carnivore, (u32, u32), (u32, u32))>,
+#[derive(Clone, Debug)]
+pub struct Island {
+    map_vec: Vec<String>,
+    height: usize,
+    width: usize,
 
-    pop_in_cell: HashMap<String, HashMap<(u32, u32), u32>>,
-    pop: HashMap<String, u32>,
+    // row-major grid, the cell at (x, y) is at index y * width + x
+    cells: Vec<Cell>,
+
+    species: SpeciesRegistry,
+    genetics: Option<Genetics>,
+    reproduction: Reproduction,
+    landscape_params: LandscapeParameters,
+    climate: Vec<ClimateEvent>,
+    disease: BTreeMap<SpeciesId, DiseaseParams>,
+    events: Vec<Event>,
+    // flooded lowland cells and the year they turn back into lowland
+    floods: Vec<(u32, Vec<usize>)>,
+    // the events applied in the last yearly cycle
+    event_log: Vec<EventRecord>,
+    terrain: Option<TerrainDynamics>,
+    migration: MigrationStrategies,
+
+    // master seed, every cell and event derives its own stream from it via cell_rng
+    seed: u64,
+    // number of yearly cycles run, part of the seed of every cell's rng stream
+    year: u32,
 }
 
-impl Island<'_> {
-    pub fn build(raw_str: &str) -> Result<Island, Box<dyn Error>> {
+impl Island {
+    pub fn build(raw_str: &str, seed: u64) -> Result<Island, BioSimError> {
         let map_vec = Island::raw_map_to_vec(raw_str)?;
+        Island::check_water_border(&map_vec)?;
         let height = map_vec.len();
-        let width = map_vec[0].len();
-        let map = Island::vec_to_map(&map_vec);
-        let map_clone = map.clone();
-
-        let pop_in_cell: HashMap<String, HashMap<(u32, u32), u32>> = HashMap::new();
-        let pop: HashMap<String, u32> = HashMap::new();
+        let width = map_vec.first().map_or(0, |line| line.chars().count());
+        let cells = Island::vec_to_cells(&map_vec)?;
 
         let island = Island {
-            raw_str: raw_str.to_string(),
-            map_vec,
+            map_vec: map_vec.iter().map(|line| line.to_string()).collect(),
             height,
             width,
-            map,
-            moving_herb_vec: Vec::new(),
-            moving_carn_vec: Vec::new(),
-            pop_in_cell,
-            pop,
+            cells,
+            species: SpeciesRegistry::default(),
+            genetics: None,
+            reproduction: Reproduction::Asexual,
+            landscape_params: LandscapeParameters::default(),
+            climate: Vec::new(),
+            disease: BTreeMap::new(),
+            events: Vec::new(),
+            floods: Vec::new(),
+            event_log: Vec::new(),
+            terrain: None,
+            migration: MigrationStrategies::default(),
+            seed,
+            year: 0,
         };
 
         Ok(island)
     }
 
-    pub fn map_vec(&self) -> &Vec<&str> {
+    pub fn map_vec(&self) -> &Vec<String> {
         &self.map_vec
     }
 
-    pub fn map(&self) -> &HashMap<(u32, u32), Cell> {
-        &self.map
+    pub fn height(&self) -> usize {
+        self.height
     }
 
-    pub fn raw_map_to_vec(input_str: &str) -> Result<Vec<&str>, &'static str> {
-        let mut lines = input_str.lines().filter(|line| !line.trim().is_empty());
+    pub fn width(&self) -> usize {
+        self.width
+    }
 
-        let line_len = lines.next().map_or(0, |line| line.trim().len());
+    // the number of yearly cycles run so far
+    pub fn year(&self) -> u32 {
+        self.year
+    }
 
-        input_str
-            .lines()
-            .filter(|line| !line.trim().is_empty())
-            .map(|line| {
-                let line = line.trim();
-                if line.len() != line_len {
-                    return Err("Lines are not the same length");
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    fn index(&self, loc: (u32, u32)) -> Option<usize> {
+        let (x, y) = (loc.0 as usize, loc.1 as usize);
+
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn cell(&self, loc: (u32, u32)) -> Option<&Cell> {
+        self.index(loc).map(|i| &self.cells[i])
+    }
+
+    pub fn cell_mut(&mut self, loc: (u32, u32)) -> Option<&mut Cell> {
+        self.index(loc).map(|i| &mut self.cells[i])
+    }
+
+    // the cells north, east, south and west of loc that are on the map
+    pub fn neighbours(&self, loc: (u32, u32)) -> impl Iterator<Item = (u32, u32)> + '_ {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| {
+                let x = loc.0.checked_add_signed(dx)?;
+                let y = loc.1.checked_add_signed(dy)?;
+                self.index((x, y)).map(|_| (x, y))
+            })
+    }
+
+    pub fn species(&self) -> &SpeciesRegistry {
+        &self.species
+    }
+
+    pub fn register_species(&mut self, entry: &SpeciesEntry) -> Result<SpeciesId, BioSimError> {
+        self.species.register_entry(entry)
+    }
+
+    pub fn genetics(&self) -> Option<&Genetics> {
+        self.genetics.as_ref()
+    }
+
+    // with genetics, newborns inherit mutated parameters from their parent
+    pub fn set_genetics(&mut self, genetics: Option<Genetics>) -> Result<(), BioSimError> {
+        if let Some(genetics) = &genetics {
+            genetics.validate()?;
+        }
+
+        self.genetics = genetics;
+        Ok(())
+    }
+
+    pub fn reproduction(&self) -> Reproduction {
+        self.reproduction
+    }
+
+    pub fn set_reproduction(&mut self, reproduction: Reproduction) {
+        self.reproduction = reproduction;
+    }
+
+    // mean and variance of every heritable parameter, for every species with animals
+    pub fn trait_stats(&self) -> Vec<TraitStats> {
+        let Some(genetics) = &self.genetics else {
+            return Vec::new();
+        };
+
+        let mut stats = Vec::new();
+        for species in self.species.ids() {
+            let animals: Vec<&Animal> = self
+                .cells
+                .iter()
+                .filter_map(|cell| cell.fauna.as_ref())
+                .flat_map(|fauna| fauna.get(species))
+                .collect();
+
+            if animals.is_empty() {
+                continue;
+            }
+
+            for key in genetics.traits() {
+                let values: Vec<f32> = animals
+                    .iter()
+                    .filter_map(|animal| animal.params.get(key))
+                    .collect();
+                let mean = values.iter().sum::<f32>() / values.len() as f32;
+                let variance =
+                    values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32;
+
+                stats.push(TraitStats {
+                    species,
+                    key: key.to_string(),
+                    mean,
+                    variance,
+                });
+            }
+        }
+
+        stats
+    }
+
+    // update some parameters of a species, for both new and existing animals. Living
+    // animals only get the given keys, so traits that evolved under genetics are kept.
+    pub fn set_animal_parameters(
+        &mut self,
+        species: SpeciesId,
+        partial_params: &HashMap<String, f32>,
+    ) -> Result<(), BioSimError> {
+        self.species.set(species, partial_params)?;
+
+        for fauna in self.cells.iter_mut().filter_map(|cell| cell.fauna.as_mut()) {
+            for animal in fauna.get_mut(species).iter_mut() {
+                let params = animal.params.update(partial_params)?;
+                animal.set_params(params);
+            }
+        }
+
+        Ok(())
+    }
+
+    // adds animals to the island, also between two yearly cycles.
+    // The whole population is checked first, so nothing is added if any entry is invalid.
+    pub fn add_population(&mut self, population: &[PopulationEntry]) -> Result<(), BioSimError> {
+        for entry in population {
+            let cell = self
+                .cell(entry.loc)
+                .ok_or(BioSimError::OutOfBounds(entry.loc))?;
+
+            if cell.fauna.is_none() {
+                return Err(BioSimError::AnimalsInWater(entry.loc));
+            }
+
+            for animal in &entry.pop {
+                self.species.id(&animal.species)?;
+
+                if let Some(weight) = animal.weight {
+                    if !weight.is_finite() || weight <= 0.0 {
+                        return Err(BioSimError::InvalidParameter {
+                            key: "weight".to_string(),
+                            value: weight,
+                            reason: "must be a positive number",
+                        });
+                    }
                 }
-                if !line.chars().all(|c| ISLAND.allowed_cells.contains(&c)) {
-                    return Err("Invalid character in line");
+            }
+        }
+
+        for entry in population {
+            // the locations were checked above
+            let index = self.index(entry.loc).unwrap();
+            let cell = &mut self.cells[index];
+            for animal in &entry.pop {
+                let (species, stats) = (&animal.species, animal.stats());
+                if animal.sex.is_some() {
+                    cell.add_identical_animals(species, stats, animal.count, &self.species)?;
+                } else {
+                    cell.add_animals(species, stats, animal.count, &self.species)?;
                 }
-                Ok(line)
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn add_animals(
+        &mut self,
+        loc: (u32, u32),
+        species: &str,
+        stats: Stats,
+        amount: u32,
+    ) -> Result<(), BioSimError> {
+        let index = self.index(loc).ok_or(BioSimError::OutOfBounds(loc))?;
+
+        self.cells[index].add_animals(species, stats, amount, &self.species)
+    }
+
+    pub fn landscape_params(&self) -> &LandscapeParameters {
+        &self.landscape_params
+    }
+
+    // existing cells of this type use the new f_max from their next fodder reset
+    pub fn set_landscape_parameters(
+        &mut self,
+        cell_type: CellType,
+        f_max: f32,
+    ) -> Result<(), BioSimError> {
+        self.landscape_params.set_f_max(cell_type, f_max)?;
+
+        self.cells
+            .iter_mut()
+            .filter(|cell| cell.get_cell() == cell_type)
+            .for_each(|cell| cell.set_f_max(f_max));
+
+        Ok(())
+    }
+
+    // applies to the existing cells of this type from their next regrowth
+    pub fn set_regrowth(
+        &mut self,
+        cell_type: CellType,
+        regrowth: Regrowth,
+    ) -> Result<(), BioSimError> {
+        self.landscape_params.set_regrowth(cell_type, regrowth)?;
+
+        self.cells
+            .iter_mut()
+            .filter(|cell| cell.get_cell() == cell_type)
+            .for_each(|cell| cell.set_regrowth(regrowth));
+
+        Ok(())
+    }
+
+    pub fn disease(&self) -> &BTreeMap<SpeciesId, DiseaseParams> {
+        &self.disease
+    }
+
+    pub fn set_disease(
+        &mut self,
+        species: SpeciesId,
+        disease: DiseaseParams,
+    ) -> Result<(), BioSimError> {
+        disease.validate()?;
+
+        self.disease.insert(species, disease);
+        Ok(())
+    }
+
+    // island-wide counts for every species with a disease
+    pub fn infection_counts(&self) -> Vec<(SpeciesId, InfectionCounts)> {
+        self.disease
+            .keys()
+            .map(|&species| {
+                let mut total = InfectionCounts::default();
+                for cell in &self.cells {
+                    let counts = cell.infection_counts(species);
+                    total.susceptible += counts.susceptible;
+                    total.infected += counts.infected;
+                    total.recovered += counts.recovered;
+                }
+                (species, total)
             })
             .collect()
     }
 
-    fn vec_to_map<'a>(map_vec: &Vec<&str>) -> HashMap<(u32, u32), Cell> {
-        map_vec
+    // like infection_counts, per cell and leaving out species absent from a cell
+    pub fn infection_counts_per_cell(&self) -> Vec<((u32, u32), SpeciesId, InfectionCounts)> {
+        self.cells
             .iter()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.chars().enumerate().map(move |(x, cell)| {
-                    (
-                        (x as u32, y as u32),
-                        crate::cell::from_char(cell, (x as u32, y as u32)),
-                    )
+            .flat_map(|cell| {
+                self.disease.keys().filter_map(|&species| {
+                    let counts = cell.infection_counts(species);
+                    (counts.total() > 0).then_some((cell.loc, species, counts))
                 })
             })
             .collect()
     }
 
-    pub fn get_pop(&self) -> (usize, usize) {
-        let mut herb_count = 0;
-        let mut carn_count = 0;
+    pub fn migration(&self) -> &MigrationStrategies {
+        &self.migration
+    }
+
+    // how animals of the species pick the neighbour they migrate to
+    pub fn set_migration_strategy(
+        &mut self,
+        species: SpeciesId,
+        strategy: Arc<dyn MigrationStrategy>,
+    ) {
+        self.migration.set(species, strategy);
+    }
+
+    pub fn terrain(&self) -> Option<&TerrainDynamics> {
+        self.terrain.as_ref()
+    }
+
+    // without terrain dynamics the cell types only change with floods
+    pub fn set_terrain(&mut self, terrain: Option<TerrainDynamics>) -> Result<(), BioSimError> {
+        if let Some(terrain) = &terrain {
+            terrain.validate()?;
+        }
+
+        self.terrain = terrain;
+        Ok(())
+    }
 
-        for (_, cell) in self.map.iter() {
-            if let Some(fauna) = cell.fauna.as_ref() {
-                herb_count += fauna.herbivore.len();
-                carn_count += fauna.carnivore.len();
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    // replaces the schedule, floods already in progress still recede as planned
+    pub fn set_events(&mut self, events: Vec<Event>) -> Result<(), BioSimError> {
+        for event in &events {
+            event.validate()?;
+            if let Event::Cull { species, .. } = event {
+                self.species.id(species)?;
             }
         }
 
-        (herb_count, carn_count)
+        self.events = events;
+        Ok(())
     }
 
-    pub fn move_all_animals(&mut self, cell: &mut Cell) {
-        let mut move_index = Vec::new();
+    pub fn applied_events(&self) -> &[EventRecord] {
+        &self.event_log
+    }
 
-        for (i, herb) in cell.fauna.as_ref().unwrap().herbivore.iter().enumerate() {
-            if let Some(_) = herb.stats_as_ref().move_to {
-                move_index.push(i);
+    // the scheduled events of the coming year and the floods receding in it
+    fn apply_events(&mut self) {
+        let year = self.year + 1;
+        self.event_log.clear();
+
+        let (receding, floods) = std::mem::take(&mut self.floods)
+            .into_iter()
+            .partition(|&(until, _)| until <= year);
+        self.floods = floods;
+        for (_, indices) in receding {
+            for &index in &indices {
+                self.cells[index].set_cell_type(CellType::Lowland, &self.landscape_params);
             }
+            self.event_log.push(EventRecord {
+                year,
+                kind: "flood receded",
+                cells: indices.len(),
+                killed: 0,
+                moved: 0,
+            });
         }
-        // sort move_index in reverse
-        move_index.sort_by(|a, b| b.cmp(a));
 
-        for index in move_index {
-            let herb = cell.fauna.as_mut().unwrap().herbivore.remove(index);
-            self.map
-                .get_mut(&herb.stats_as_ref().move_to.unwrap())
-                .unwrap()
-                .fauna
-                .as_mut()
-                .unwrap()
-                .herbivore
-                .push(herb);
+        let events: Vec<(usize, Event)> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| event.year() == year)
+            .map(|(number, event)| (number, event.clone()))
+            .collect();
+        for (number, event) in events {
+            // every event gets its own stream, numbered after the cell streams
+            let mut rng = Island::cell_rng(self.seed, self.year, self.cells.len() + number);
+            let (cells, killed, moved) = match event {
+                Event::Fire { region, kill, .. } => self.burn(region, kill, &mut rng),
+                Event::Flood {
+                    region,
+                    duration,
+                    drown,
+                    ..
+                } => self.flood(region, year + duration, drown, &mut rng),
+                Event::Cull {
+                    ref species,
+                    count,
+                    region,
+                    ..
+                } => {
+                    let species = self.species.id(species).unwrap();
+                    self.cull(species, count as usize, region)
+                }
+            };
+            self.event_log.push(EventRecord {
+                year,
+                kind: event.kind(),
+                cells,
+                killed,
+                moved,
+            });
         }
+    }
+
+    // returns the number of cells burnt and animals killed, and no animal moves
+    fn burn(&mut self, region: Region, kill: f32, rng: &mut StdRng) -> (usize, usize, usize) {
+        let (mut cells, mut killed) = (0, 0);
 
-        let mut move_index = Vec::new();
+        for cell in self.cells.iter_mut() {
+            if !region_contains(region, cell.loc) || cell.fauna.is_none() {
+                continue;
+            }
+            cells += 1;
+            cell.fodder = 0.0;
 
-        for (i, carn) in cell.fauna.as_ref().unwrap().carnivore.iter().enumerate() {
-            if let Some(_) = carn.stats_as_ref().move_to {
-                move_index.push(i);
+            for animals in cell.fauna.as_mut().unwrap().populations_mut() {
+                let before = animals.len();
+                animals.retain(|_| rng.gen::<f32>() >= kill);
+                killed += before - animals.len();
             }
         }
-        // sort move_index in reverse
-        move_index.sort_by(|a, b| b.cmp(a));
 
-        for index in move_index {
-            let carn = cell.fauna.as_mut().unwrap().carnivore.remove(index);
-            self.map
-                .get_mut(&carn.stats_as_ref().move_to.unwrap())
-                .unwrap()
-                .fauna
-                .as_mut()
-                .unwrap()
-                .carnivore
-                .push(carn);
+        (cells, killed, 0)
+    }
+
+    // returns the number of cells flooded, animals drowned and animals that fled
+    fn flood(
+        &mut self,
+        region: Region,
+        until: u32,
+        drown: f32,
+        rng: &mut StdRng,
+    ) -> (usize, usize, usize) {
+        let indices: Vec<usize> = (0..self.cells.len())
+            .filter(|&index| {
+                let cell = &self.cells[index];
+                cell.get_cell() == CellType::Lowland && region_contains(region, cell.loc)
+            })
+            .collect();
+
+        let mut displaced = Vec::new();
+        for &index in &indices {
+            let loc = self.cells[index].loc;
+            let animals = self.cells[index].set_cell_type(CellType::Water, &self.landscape_params);
+            displaced.extend(animals.into_iter().map(|animal| (loc, animal)));
+        }
+
+        let (mut drowned, mut fled) = (0, 0);
+        for (loc, animal) in displaced {
+            let refuges: Vec<(u32, u32)> = self
+                .neighbours(loc)
+                .filter(|&neighbour| self.accepts_migrants(neighbour))
+                .collect();
+
+            match refuges.choose(rng) {
+                Some(&refuge) if rng.gen::<f32>() >= drown => {
+                    self.cell_mut(refuge).unwrap().add_animal(animal);
+                    fled += 1;
+                }
+                _ => drowned += 1,
+            }
+        }
+
+        let flooded = indices.len();
+        if flooded > 0 {
+            self.floods.push((until, indices));
         }
+        (flooded, drowned, fled)
     }
-    pub fn remove_moving_animals(cell: &mut Cell) -> (Vec<Herbivore>, Vec<Carnivore>) {
-        // removing herbs
-        let mut move_index = Vec::new();
 
-        for (i, herb) in cell.fauna.as_ref().unwrap().herbivore.iter().enumerate() {
-            if let Some(_) = herb.stats_as_ref().move_to {
-                move_index.push(i);
+    // removes the fittest animals of the species, returns the number of cells
+    // they were taken from and the number removed
+    fn cull(
+        &mut self,
+        species: SpeciesId,
+        count: usize,
+        region: Option<Region>,
+    ) -> (usize, usize, usize) {
+        let mut candidates: Vec<(f32, usize, usize)> = Vec::new();
+        for (index, cell) in self.cells.iter().enumerate() {
+            if region.is_some_and(|region| !region_contains(region, cell.loc)) {
+                continue;
+            }
+            if let Some(fauna) = &cell.fauna {
+                for (position, animal) in fauna.get(species).iter().enumerate() {
+                    candidates.push((animal.calc_fitness(), index, position));
+                }
             }
         }
-        // sort move_index in reverse
-        move_index.sort_by(|a, b| b.cmp(a));
-        let mut moving_herbs = Vec::new();
+        candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+        candidates.truncate(count);
+
+        let mut cells: Vec<usize> = Vec::new();
+        for &(_, index, position) in &candidates {
+            let fauna = self.cells[index].fauna.as_mut().unwrap();
+            fauna.get_mut(species)[position].stats.alive = false;
+            if !cells.contains(&index) {
+                cells.push(index);
+            }
+        }
+        for &index in &cells {
+            let fauna = self.cells[index].fauna.as_mut().unwrap();
+            fauna.get_mut(species).retain(|animal| animal.stats.alive);
+        }
 
-        for i in move_index {
-            moving_herbs.push(cell.fauna.as_mut().unwrap().herbivore.remove(i));
+        (cells.len(), candidates.len(), 0)
+    }
+
+    pub fn climate(&self) -> &[ClimateEvent] {
+        &self.climate
+    }
+
+    // replaces the schedule, it is applied from the next yearly cycle on
+    pub fn set_climate(&mut self, climate: Vec<ClimateEvent>) -> Result<(), BioSimError> {
+        for event in &climate {
+            event.validate()?;
         }
 
-        // removing carns
-        let mut move_index = Vec::new();
+        self.climate = climate;
+        Ok(())
+    }
 
-        for (i, carn) in cell.fauna.as_ref().unwrap().carnivore.iter().enumerate() {
-            if let Some(_) = carn.stats_as_ref().move_to {
-                move_index.push(i);
-            }
+    // the events active in the cycle that is logged as the given year
+    pub fn active_climate(&self, year: u32) -> impl Iterator<Item = &ClimateEvent> {
+        self.climate
+            .iter()
+            .filter(move |event| event.is_active(year))
+    }
+
+    fn apply_climate(&mut self) {
+        let year = self.year + 1;
+        let active: Vec<&ClimateEvent> = self
+            .climate
+            .iter()
+            .filter(|event| event.is_active(year))
+            .collect();
+
+        for cell in &mut self.cells {
+            let mut factors = ClimateFactors::default();
+            active
+                .iter()
+                .filter(|event| event.applies_to(cell))
+                .for_each(|event| factors.apply(event));
+            cell.set_climate(factors);
         }
+    }
+
+    pub fn raw_map_to_vec(input_str: &str) -> Result<Vec<&str>, BioSimError> {
+        let mut lines = input_str.lines().filter(|line| !line.trim().is_empty());
 
-        move_index.sort_by(|a, b| b.cmp(a));
-        let mut moving_carns = Vec::new();
+        let line_len = lines
+            .next()
+            .map(|line| line.trim().chars().count())
+            .ok_or(BioSimError::EmptyMap)?;
 
-        for i in move_index {
-            moving_carns.push(cell.fauna.as_mut().unwrap().carnivore.remove(i));
+        input_str
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                let line = line.trim();
+                let found = line.chars().count();
+                if found != line_len {
+                    return Err(BioSimError::RaggedMap {
+                        line: i + 1,
+                        expected: line_len,
+                        found,
+                    });
+                }
+                if let Some((col, ch)) = line
+                    .chars()
+                    .enumerate()
+                    .find(|(_, c)| !ISLAND.allowed_cells.contains(c))
+                {
+                    return Err(BioSimError::InvalidCell {
+                        line: i + 1,
+                        col: col + 1,
+                        ch,
+                    });
+                }
+                Ok(line)
+            })
+            .collect()
+    }
+
+    // every cell on the outer ring must be water, so migration never leaves the map
+    fn check_water_border(map_vec: &[&str]) -> Result<(), BioSimError> {
+        let height = map_vec.len();
+        let mut positions = Vec::new();
+
+        for (y, line) in map_vec.iter().enumerate() {
+            let width = line.chars().count();
+            for (x, c) in line.chars().enumerate() {
+                let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+                if on_border && c != 'W' {
+                    positions.push((x as u32, y as u32));
+                }
+            }
         }
 
-        (moving_herbs, moving_carns)
+        if positions.is_empty() {
+            Ok(())
+        } else {
+            Err(BioSimError::NonWaterBorder(positions))
+        }
     }
 
-    pub fn yearly_cycle(&mut self) {
-        //instead of looping through all the cells here, to it in the individual methods.
-        //let cells: Vec<_> = self.map.values_mut().collect();
+    // surrounds the map with a ring of water if its border is not all water,
+    // which shifts every location by (1, 1). Returns None if no padding is needed.
+    pub fn pad_with_water(raw_str: &str) -> Result<Option<String>, BioSimError> {
+        let map_vec = Island::raw_map_to_vec(raw_str)?;
 
-        let coordinates: Vec<_> = self.map.keys().map(|&x| x.clone()).collect();
+        if Island::check_water_border(&map_vec).is_ok() {
+            return Ok(None);
+        }
 
-        let mut moving_herbs = Vec::new();
-        let mut moving_carns = Vec::new();
+        let water_line = "W".repeat(map_vec[0].chars().count() + 2);
+        let mut padded = vec![water_line.clone()];
+        padded.extend(map_vec.iter().map(|line| format!("W{line}W")));
+        padded.push(water_line);
 
-        for (coordinate, cell) in self.map.iter_mut() {
-            if (cell.get_cell() == CellType::Water) {
-                continue;
+        Ok(Some(padded.join("\n")))
+    }
+
+    fn vec_to_cells(map_vec: &[&str]) -> Result<Vec<Cell>, BioSimError> {
+        map_vec
+            .iter()
+            .enumerate()
+            .flat_map(|(y, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(x, cell)| crate::cell::from_char(cell, (x as u32, y as u32)))
+            })
+            .collect()
+    }
+
+    // number of animals of every species, indexed by SpeciesId
+    pub fn get_pop(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.species.ids().count()];
+
+        for fauna in self.cells.iter().filter_map(|cell| cell.fauna.as_ref()) {
+            for species in self.species.ids() {
+                counts[species.index()] += fauna.count(species);
             }
-            cell.add_newborns();
-            cell.feed_animals();
-            cell.get_moving_animals();
-            cell.age_animals();
-            cell.loss_of_weight();
-            cell.animal_death();
-            cell.reset_fodder();
-            (moving_herbs, moving_carns) = Island::remove_moving_animals(cell);
-        }
-
-        moving_herbs.retain(|herb| {
-            let move_to = herb.stats_as_ref().move_to.unwrap();
-            self.map.get(&move_to).unwrap().get_cell() != CellType::Water
-        });
-
-        moving_carns.retain(|carn| {
-            let move_to = carn.stats_as_ref().move_to.unwrap();
-            self.map.get(&move_to).unwrap().get_cell() != CellType::Water
-        });
-
-        // move all herbs
-        while !moving_herbs.is_empty() {
-            let move_to = moving_herbs.last().unwrap().stats_as_ref().move_to.unwrap();
-
-            if (self.map.get(&move_to).unwrap().get_cell() == CellType::Water) {
-                continue;
+        }
+
+        counts
+    }
+
+    pub fn count(&self, species: SpeciesId) -> usize {
+        self.cells
+            .iter()
+            .filter_map(|cell| cell.fauna.as_ref())
+            .map(|fauna| fauna.count(species))
+            .sum()
+    }
+
+    // number of animals of every species, indexed by SpeciesId, for every cell with fauna,
+    // in row-major order
+    pub fn get_pop_per_cell(&self) -> Vec<((u32, u32), Vec<usize>)> {
+        self.cells
+            .iter()
+            .filter_map(|cell| {
+                let fauna = cell.fauna.as_ref()?;
+                let counts = self.species.ids().map(|id| fauna.count(id)).collect();
+                Some((cell.loc, counts))
+            })
+            .collect()
+    }
+
+    // takes the animals with a move_to out of the cell, the others stay
+    pub fn remove_moving_animals(cell: &mut Cell) -> Vec<Animal> {
+        let Some(fauna) = cell.fauna.as_mut() else {
+            return Vec::new();
+        };
+
+        let mut moving = Vec::new();
+        for animals in fauna.populations_mut() {
+            let (moving_animals, staying_animals) = std::mem::take(animals)
+                .into_iter()
+                .partition(|animal: &Animal| animal.stats_as_ref().move_to.is_some());
+            *animals = staying_animals;
+            moving.extend(moving_animals);
+        }
+
+        moving
+    }
+
+    // animals can only move to land cells on the map
+    fn accepts_migrants(&self, loc: (u32, u32)) -> bool {
+        self.cell(loc)
+            .is_some_and(|cell| cell.get_cell() != CellType::Water)
+    }
+
+    // two-phase migration: the emigrants of every cell are collected before any of them
+    // arrive, so no animal moves twice in a year. An animal whose move_to is water or off
+    // the map stays in its own cell.
+    fn migrate_animals(&mut self) {
+        let mut arriving = Vec::new();
+
+        for index in 0..self.cells.len() {
+            let origin = self.cells[index].loc;
+
+            for mut animal in Island::remove_moving_animals(&mut self.cells[index]) {
+                let move_to = animal.stats_as_mut().move_to.take().unwrap();
+                let destination = if self.accepts_migrants(move_to) {
+                    move_to
+                } else {
+                    origin
+                };
+                arriving.push((destination, animal));
             }
+        }
+
+        for (destination, animal) in arriving {
+            self.cell_mut(destination).unwrap().add_animal(animal);
+        }
+    }
+
+    // every cell draws from its own rng stream, seeded from the island seed, the year
+    // and the cell index, so a run does not depend on the order cells are processed in
+    fn cell_rng(seed: u64, year: u32, index: usize) -> StdRng {
+        // splitmix64 finalizer, spreads nearby inputs over the whole seed space
+        let mut z = seed
+            ^ (year as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (index as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        StdRng::seed_from_u64(z ^ (z >> 31))
+    }
+
+    fn run_cell_cycles(&mut self) {
+        let (seed, year) = (self.seed, self.year);
+        let surroundings = if self.migration.needs_surroundings() {
+            Surroundings::new(&self.cells, self.width, &self.species)
+        } else {
+            Surroundings::default()
+        };
+        let cycle = CellCycle {
+            species: &self.species,
+            genetics: self.genetics.as_ref(),
+            reproduction: self.reproduction,
+            disease: &self.disease,
+            terrain: self.terrain.as_ref(),
+            migration: &self.migration,
+            surroundings: &surroundings,
+        };
+        let run = |(index, cell): (usize, &mut Cell)| {
+            let mut rng = Island::cell_rng(seed, year, index);
+            cycle.run(cell, &mut rng);
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        self.cells.iter_mut().enumerate().for_each(run);
+
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.cells.par_iter_mut().enumerate().for_each(run);
+        }
+    }
+
+    // cells whose grazing record calls for it turn into the next terrain type
+    fn apply_terrain(&mut self) {
+        let Some(terrain) = self.terrain else {
+            return;
+        };
 
-            let herb = moving_herbs.pop().unwrap();
-            self.map.get_mut(&move_to).unwrap().add_herb_struct(herb);
+        for cell in &mut self.cells {
+            if let Some(cell_type) =
+                terrain.next_type(cell.get_cell(), cell.origin(), &cell.terrain())
+            {
+                cell.set_cell_type(cell_type, &self.landscape_params);
+            }
         }
+    }
+
+    // the current cell types as map lines, changed by floods and terrain dynamics
+    pub fn terrain_map(&self) -> Vec<String> {
+        self.cells
+            .chunks(self.width)
+            .map(|row| row.iter().map(|cell| cell.get_cell().symbol()).collect())
+            .collect()
+    }
+
+    pub fn yearly_cycle(&mut self) {
+        self.apply_events();
+        self.apply_climate();
+        self.run_cell_cycles();
+        self.apply_terrain();
+        self.year += 1;
 
-        // move all carns
-        while !moving_carns.is_empty() {
-            let carn = moving_carns.pop().unwrap();
-            let move_to = carn.stats_as_ref().move_to.unwrap();
-            self.map.get_mut(&move_to).unwrap().add_carn_struct(carn);
+        self.migrate_animals();
+    }
+}
+
+// the island-wide settings read by the yearly phases that only touch a single cell
+struct CellCycle<'a> {
+    species: &'a SpeciesRegistry,
+    genetics: Option<&'a Genetics>,
+    reproduction: Reproduction,
+    disease: &'a BTreeMap<SpeciesId, DiseaseParams>,
+    terrain: Option<&'a TerrainDynamics>,
+    migration: &'a MigrationStrategies,
+    surroundings: &'a Surroundings,
+}
+
+impl CellCycle<'_> {
+    fn run(&self, cell: &mut Cell, rng: &mut StdRng) {
+        if cell.get_cell() == CellType::Water {
+            return;
+        }
+        cell.add_newborns(self.genetics, self.reproduction, rng);
+        let demand = self.terrain.map(|_| cell.grazing_demand(self.species));
+        cell.feed_animals(self.species, rng);
+        if let (Some(terrain), Some(demand)) = (self.terrain, demand) {
+            cell.record_grazing(terrain, demand);
         }
+        cell.get_moving_animals(self.migration, self.surroundings, rng);
+        cell.age_animals();
+        cell.loss_of_weight();
+        cell.spread_disease(self.disease, rng);
+        cell.animal_death(rng);
+        cell.regrow_fodder();
     }
 }
 
 #[cfg(test)]
 mod island_tests {
-    use crate::cell::Fauna;
     use crate::cell::{self};
 
     use super::*;
@@ -270,7 +987,7 @@ WWW";
         println!("{input_str}");
         let correct = vec!["WWW", "WHW", "WLW", "WWW"];
 
-        let island = Island::build(input_str).unwrap();
+        let island = Island::build(input_str, 1).unwrap();
 
         let map_vec = island.map_vec();
 
@@ -286,7 +1003,7 @@ WWW";
         WLW
         WWW";
 
-        let _ = Island::build(input_str).unwrap();
+        let _ = Island::build(input_str, 1).unwrap();
     }
 
     #[test]
@@ -298,7 +1015,59 @@ WWW";
         WLW
         WWW";
 
-        let _ = Island::build(input_str).unwrap();
+        let _ = Island::build(input_str, 1).unwrap();
+    }
+
+    #[test]
+    fn test_map_errors() {
+        let err = Island::build("WWW\nWEW\nWWW", 1).unwrap_err();
+        assert!(matches!(
+            err,
+            BioSimError::InvalidCell {
+                line: 2,
+                col: 2,
+                ch: 'E'
+            }
+        ));
+
+        let err = Island::build("WWWW\nWLW\nWWWW", 1).unwrap_err();
+        assert!(matches!(
+            err,
+            BioSimError::RaggedMap {
+                line: 2,
+                expected: 4,
+                found: 3
+            }
+        ));
+
+        let err = Island::build("\n  \n", 1).unwrap_err();
+        assert!(matches!(err, BioSimError::EmptyMap));
+    }
+
+    #[test]
+    fn test_land_on_border() {
+        let err = Island::build("L", 1).unwrap_err();
+        assert!(matches!(err, BioSimError::NonWaterBorder(positions) if positions == vec![(0, 0)]));
+
+        let input_str = "
+        WWWW
+        WLLH
+        WWLW";
+        let err = Island::build(input_str, 1).unwrap_err();
+        assert!(
+            matches!(err, BioSimError::NonWaterBorder(positions) if positions == vec![(3, 1), (2, 2)])
+        );
+    }
+
+    #[test]
+    fn test_pad_with_water() {
+        let padded = Island::pad_with_water("WHW").unwrap().unwrap();
+        assert_eq!(padded, "WWWWW\nWWHWW\nWWWWW");
+
+        let island = Island::build(&padded, 1).unwrap();
+        assert_eq!(island.cell((2, 1)).unwrap().get_cell(), CellType::Highland);
+
+        assert_eq!(Island::pad_with_water("WWW\nWLW\nWWW").unwrap(), None);
     }
 
     #[test]
@@ -308,11 +1077,11 @@ WWW";
         WDHLW
         WWWWW";
 
-        let island = Island::build(input_str).unwrap();
+        let island = Island::build(input_str, 1).unwrap();
 
-        let island_map = island.map().clone();
+        let island_map = island.cells().to_vec();
 
-        let map_vec: HashMap<(u32, u32), Cell> = [
+        let expected_cells = [
             ((0, 0), cell::water((0, 0))),
             ((1, 0), cell::water((1, 0))),
             ((2, 0), cell::water((2, 0))),
@@ -328,33 +1097,736 @@ WWW";
             ((2, 2), cell::water((2, 2))),
             ((3, 2), cell::water((3, 2))),
             ((4, 2), cell::water((4, 2))),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let expected = HashMap::from(map_vec);
+        ];
+
+        for (loc, cell) in expected_cells.iter() {
+            assert_eq!(
+                island.cell(*loc),
+                Some(cell),
+                "Cell at {loc:?} is not equal"
+            );
+        }
 
-        assert_eq!(island_map, expected, "Hashmap of map is not equal");
+        let expected: Vec<Cell> = expected_cells.into_iter().map(|(_, cell)| cell).collect();
+        assert_eq!(
+            island_map, expected,
+            "Cells of map are not in row-major order"
+        );
     }
 
     #[test]
     fn add_anim_struct() {
-        let input_str = "L";
-
-        let island = Island::build(input_str).unwrap();
+        let input_str = "
+        WWW
+        WLW
+        WWW";
 
-        let mut map = island.map;
+        let mut island = Island::build(input_str, 1).unwrap();
 
-        let cell = map.get_mut(&(0, 0)).unwrap();
+        let cell = island.cell_mut((1, 1)).unwrap();
 
-        //cell.add_carn_struct(Carnivore::new());
-        cell.add_carn_struct(Carnivore::new());
-        cell.add_herb_struct(Herbivore::new());
+        //cell.add_animal(Animal::carnivore());
+        cell.add_animal(Animal::carnivore());
+        cell.add_animal(Animal::herbivore());
 
         println!("{:#?}", cell.fauna);
 
-        assert_eq!(cell.fauna.as_ref().unwrap().herbivore.len(), 1);
-        assert_eq!(cell.fauna.as_ref().unwrap().carnivore.len(), 1);
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::HERBIVORE), 1);
+        assert_eq!(cell.fauna.as_ref().unwrap().count(SpeciesId::CARNIVORE), 1);
+    }
+
+    #[test]
+    fn set_animal_parameters_updates_existing_animals() {
+        let input_str = "
+        WWW
+        WLW
+        WWW";
+
+        let mut island = Island::build(input_str, 1).unwrap();
+        let cell = island.cell_mut((1, 1)).unwrap();
+        cell.add_animal(Animal::herbivore());
+        cell.add_animal(Animal::carnivore());
+
+        let partial = HashMap::from([("omega".to_string(), 0.1)]);
+        island
+            .set_animal_parameters(SpeciesId::CARNIVORE, &partial)
+            .unwrap();
+
+        let fauna = island.cell((1, 1)).unwrap().fauna.as_ref().unwrap();
+        assert_eq!(fauna.get(SpeciesId::CARNIVORE)[0].params.omega, 0.1);
+        assert_eq!(fauna.get(SpeciesId::HERBIVORE)[0].params.omega, 0.4);
+        assert_eq!(island.species().params(SpeciesId::CARNIVORE).omega, 0.1);
+    }
+
+    #[test]
+    fn set_animal_parameters_rejects_unknown_key() {
+        let mut island = Island::build("WWW\nWLW\nWWW", 1).unwrap();
+        let partial = HashMap::from([("speed".to_string(), 1.0)]);
+
+        assert!(island
+            .set_animal_parameters(SpeciesId::HERBIVORE, &partial)
+            .is_err());
+    }
+
+    #[test]
+    fn set_landscape_parameters_updates_cells() {
+        let input_str = "
+        WWWW
+        WLHW
+        WWWW";
+
+        let mut island = Island::build(input_str, 1).unwrap();
+        island
+            .set_landscape_parameters(CellType::Lowland, 400.0)
+            .unwrap();
+        island
+            .cells_mut()
+            .iter_mut()
+            .for_each(|cell| cell.reset_fodder());
+
+        assert_eq!(island.cell((1, 1)).unwrap().fodder, 400.0);
+        assert_eq!(island.cell((2, 1)).unwrap().fodder, 300.0);
+        assert_eq!(island.landscape_params().f_max(&CellType::Lowland), 400.0);
+    }
+
+    #[test]
+    fn set_regrowth_updates_cells() {
+        let mut island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+        let regrowth = Regrowth::Logistic {
+            alpha: 0.5,
+            f_min: 0.0,
+        };
+        island.set_regrowth(CellType::Lowland, regrowth).unwrap();
+
+        assert_eq!(island.cell((1, 1)).unwrap().regrowth(), regrowth);
+        assert_eq!(island.cell((2, 1)).unwrap().regrowth(), Regrowth::Reset);
+
+        // grazed down fodder is still low the next year
+        island.cell_mut((1, 1)).unwrap().fodder = 100.0;
+        island.yearly_cycle();
+        assert!(island.cell((1, 1)).unwrap().fodder < 200.0);
+        assert!(island.set_regrowth(CellType::Water, regrowth).is_err());
+    }
+
+    #[test]
+    fn add_population_records() {
+        let mut island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+        let population = vec![
+            PopulationEntry {
+                loc: (1, 1),
+                pop: vec![
+                    AnimalEntry {
+                        species: "Herbivore".to_string(),
+                        age: Some(3),
+                        weight: Some(14.0),
+                        sex: None,
+                        infection: None,
+                        count: 1,
+                    },
+                    AnimalEntry {
+                        species: "Carnivore".to_string(),
+                        age: None,
+                        weight: None,
+                        sex: None,
+                        infection: None,
+                        count: 2,
+                    },
+                ],
+            },
+            PopulationEntry::from(((2, 1), "Herbivore".to_string(), 4)),
+        ];
+
+        island.add_population(&population).unwrap();
+
+        let fauna = island.cell((1, 1)).unwrap().fauna.as_ref().unwrap();
+        assert_eq!(fauna.get(SpeciesId::HERBIVORE)[0].stats.age, 3);
+        assert_eq!(fauna.get(SpeciesId::HERBIVORE)[0].stats.weight, 14.0);
+        assert_eq!(fauna.count(SpeciesId::CARNIVORE), 2);
+        assert_eq!(island.get_pop(), vec![5, 2, 0, 0]);
+    }
+
+    #[test]
+    fn add_population_is_all_or_nothing() {
+        let mut island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+        let population = vec![
+            PopulationEntry::from(((1, 1), "Herbivore".to_string(), 4)),
+            PopulationEntry::from(((3, 1), "Herbivore".to_string(), 4)),
+        ];
+
+        let err = island.add_population(&population).unwrap_err();
+
+        assert!(matches!(err, BioSimError::AnimalsInWater((3, 1))));
+        assert_eq!(island.get_pop(), vec![0, 0, 0, 0]);
+    }
+}
+
+#[cfg(test)]
+mod grid_tests {
+    use super::*;
+
+    #[test]
+    fn test_cell_lookup() {
+        let island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+
+        assert_eq!(island.cells().len(), 12);
+        assert_eq!(island.cell((2, 1)).unwrap().loc, (2, 1));
+        assert_eq!(island.cell((2, 1)).unwrap().get_cell(), CellType::Highland);
+        assert!(island.cell((4, 1)).is_none());
+        assert!(island.cell((1, 3)).is_none());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+
+        let neighbours: Vec<_> = island.neighbours((1, 1)).collect();
+        assert_eq!(neighbours, vec![(1, 0), (2, 1), (1, 2), (0, 1)]);
+
+        let corner: Vec<_> = island.neighbours((0, 0)).collect();
+        assert_eq!(corner, vec![(1, 0), (0, 1)]);
+
+        let edge: Vec<_> = island.neighbours((3, 2)).collect();
+        assert_eq!(edge, vec![(3, 1), (2, 2)]);
+    }
+
+    // a 1000x1000 map, land inside a ring of water
+    fn large_map() -> String {
+        let line = format!("W{}W", "L".repeat(998));
+        let mut rows = vec!["W".repeat(1000)];
+        rows.extend((0..998).map(|_| line.clone()));
+        rows.push("W".repeat(1000));
+        rows.join("\n")
+    }
+
+    #[test]
+    fn test_large_map() {
+        let island = Island::build(&large_map(), 1).unwrap();
+
+        assert_eq!(island.cells().len(), 1_000_000);
+        assert_eq!(island.cell((999, 999)).unwrap().get_cell(), CellType::Water);
+        assert_eq!(
+            island.cell((500, 998)).unwrap().get_cell(),
+            CellType::Lowland
+        );
+        assert!(island.cell((1000, 0)).is_none());
+    }
+
+    #[test]
+    fn test_yearly_cycle_large_map() {
+        let mut island = Island::build(&large_map(), 1).unwrap();
+        let population: Vec<PopulationEntry> = (1..1000)
+            .step_by(111)
+            .flat_map(|x| (1..1000).step_by(111).map(move |y| (x, y)))
+            .map(|loc| PopulationEntry::from((loc, "Herbivore".to_string(), 10)))
+            .collect();
+        island.add_population(&population).unwrap();
+
+        island.yearly_cycle();
+
+        assert!(island.get_pop()[0] > 0);
+        assert_eq!(island.year(), 1);
+        let fodder = island.cell((500, 500)).unwrap().fodder;
+        assert_eq!(fodder, island.cell((500, 500)).unwrap().f_max());
+    }
+}
+
+#[cfg(test)]
+mod cell_rng_tests {
+    use super::*;
+    use rand::Rng;
+
+    #[test]
+    fn test_cell_rng_streams() {
+        let draw = |seed, year, index| Island::cell_rng(seed, year, index).gen::<u64>();
+
+        assert_eq!(draw(1, 0, 5), draw(1, 0, 5));
+        assert_ne!(draw(1, 0, 5), draw(1, 0, 6));
+        assert_ne!(draw(1, 0, 5), draw(1, 1, 5));
+        assert_ne!(draw(1, 0, 5), draw(2, 0, 5));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_same_result_for_any_thread_count() {
+        let run = |num_threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            let mut island = Island::build("WWWWW\nWLLLW\nWLHLW\nWLLLW\nWWWWW", 7).unwrap();
+            island
+                .add_population(&[
+                    PopulationEntry::from(((1, 1), "Herbivore".to_string(), 50)),
+                    PopulationEntry::from(((2, 2), "Herbivore".to_string(), 50)),
+                    PopulationEntry::from(((3, 3), "Carnivore".to_string(), 10)),
+                ])
+                .unwrap();
+
+            pool.install(|| {
+                (0..10)
+                    .map(|_| {
+                        island.yearly_cycle();
+                        island.get_pop_per_cell()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(run(1), run(4));
+    }
+}
+
+#[cfg(test)]
+mod migration_tests {
+    use super::*;
+    use crate::migration::{Kernel, MigrationMode, Movement, Neighbourhood};
+    use std::collections::HashMap;
+
+    const MAP: &str = "WWWWW\nWLLLW\nWLLLW\nWLLLW\nWWWWW";
+
+    fn everyone_migrates(island: &mut Island) {
+        let partial = HashMap::from([("mu".to_string(), 100.0)]);
+        island
+            .set_animal_parameters(SpeciesId::HERBIVORE, &partial)
+            .unwrap();
+        island
+            .set_animal_parameters(SpeciesId::CARNIVORE, &partial)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_migration_conserves_population() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        island
+            .add_population(&[
+                PopulationEntry::from(((1, 1), "Herbivore".to_string(), 40)),
+                PopulationEntry::from(((2, 2), "Herbivore".to_string(), 40)),
+                PopulationEntry::from(((3, 3), "Carnivore".to_string(), 20)),
+            ])
+            .unwrap();
+        everyone_migrates(&mut island);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10 {
+            island
+                .cells_mut()
+                .iter_mut()
+                .filter(|cell| cell.fauna.is_some())
+                .for_each(|cell| {
+                    cell.get_moving_animals(
+                        &MigrationStrategies::default(),
+                        &Surroundings::default(),
+                        &mut rng,
+                    )
+                });
+            island.migrate_animals();
+
+            assert_eq!(island.get_pop(), vec![80, 20, 0, 0]);
+        }
+    }
+
+    #[test]
+    fn test_animals_move_at_most_one_cell() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        island
+            .add_population(&[PopulationEntry::from((
+                (2, 2),
+                "Herbivore".to_string(),
+                100,
+            ))])
+            .unwrap();
+        everyone_migrates(&mut island);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        island.cell_mut((2, 2)).unwrap().get_moving_animals(
+            &MigrationStrategies::default(),
+            &Surroundings::default(),
+            &mut rng,
+        );
+        island.migrate_animals();
+
+        let neighbours: Vec<_> = island.neighbours((2, 2)).collect();
+        for (loc, counts) in island.get_pop_per_cell() {
+            if counts[SpeciesId::HERBIVORE.index()] > 0 {
+                assert!(neighbours.contains(&loc), "herbivores moved to {loc:?}");
+            }
+        }
+        assert_eq!(island.get_pop(), vec![100, 0, 0, 0]);
+        assert!(island
+            .cells()
+            .iter()
+            .filter_map(|cell| cell.fauna.as_ref())
+            .flat_map(|fauna| fauna.animals())
+            .all(|herb| herb.stats.move_to.is_none()));
+    }
+
+    #[test]
+    fn test_moves_into_water_are_rejected() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        let cell = island.cell_mut((1, 1)).unwrap();
+
+        let mut to_water = Animal::herbivore();
+        to_water.stats.move_to = Some((0, 1));
+        cell.add_animal(to_water);
+
+        let mut off_map = Animal::carnivore();
+        off_map.stats.move_to = Some((9, 9));
+        cell.add_animal(off_map);
+
+        let mut to_land = Animal::herbivore();
+        to_land.stats.move_to = Some((2, 1));
+        cell.add_animal(to_land);
+
+        island.migrate_animals();
+
+        let pop = island.get_pop_per_cell();
+        assert!(pop.contains(&((1, 1), vec![1, 1, 0, 0])), "{pop:?}");
+        assert!(pop.contains(&((2, 1), vec![1, 0, 0, 0])), "{pop:?}");
+        assert_eq!(island.get_pop(), vec![2, 1, 0, 0]);
+    }
+
+    #[test]
+    fn test_yearly_cycle_keeps_migrants() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        island
+            .add_population(&[PopulationEntry::from(((2, 2), "Herbivore".to_string(), 50))])
+            .unwrap();
+        everyone_migrates(&mut island);
+
+        island.yearly_cycle();
+
+        let herb_count = island.count(SpeciesId::HERBIVORE);
+        let stayed = island
+            .cell((2, 2))
+            .unwrap()
+            .fauna
+            .as_ref()
+            .unwrap()
+            .count(SpeciesId::HERBIVORE);
+        assert!(herb_count >= 50, "{herb_count} herbivores left");
+        assert!(stayed < herb_count, "no herbivore left (2, 2)");
+    }
+
+    #[test]
+    fn test_guided_herbivores_avoid_the_desert() {
+        let mut island = Island::build("WWWWW\nWDLHW\nWWWWW", 1).unwrap();
+        island
+            .add_population(&[PopulationEntry::from(((2, 1), "Herbivore".to_string(), 50))])
+            .unwrap();
+        everyone_migrates(&mut island);
+        island.set_migration_strategy(SpeciesId::HERBIVORE, MigrationMode::Guided.strategy());
+
+        island.yearly_cycle();
+
+        let count = |loc| {
+            island
+                .cell(loc)
+                .unwrap()
+                .fauna
+                .as_ref()
+                .map_or(0, |fauna| fauna.count(SpeciesId::HERBIVORE))
+        };
+        assert_eq!(count((1, 1)), 0);
+        assert!(count((3, 1)) > 0);
+    }
+
+    #[test]
+    fn test_dispersal_crosses_water() {
+        let map = "WWWWWWW\nWLWLLLW\nWWWLLLW\nWWWLLLW\nWWWWWWW";
+        let mut island = Island::build(map, 1).unwrap();
+        island
+            .add_population(&[PopulationEntry::from((
+                (1, 1),
+                "Herbivore".to_string(),
+                100,
+            ))])
+            .unwrap();
+        everyone_migrates(&mut island);
+        let movement = Movement {
+            neighbourhood: Neighbourhood::VonNeumann,
+            dispersal: Some(Kernel::Exponential { mean: 3.0 }),
+        };
+        let strategy = movement.strategy(MigrationMode::Random).unwrap();
+        island.set_migration_strategy(SpeciesId::HERBIVORE, strategy);
+
+        island.yearly_cycle();
+
+        let stayed = island
+            .cell((1, 1))
+            .unwrap()
+            .fauna
+            .as_ref()
+            .unwrap()
+            .count(SpeciesId::HERBIVORE);
+        assert!(stayed < island.count(SpeciesId::HERBIVORE));
+    }
+}
+
+#[cfg(test)]
+mod genetics_tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn genetics() -> Genetics {
+        Genetics {
+            mutation: BTreeMap::from([("f".to_string(), 1.0), ("mu".to_string(), 0.05)]),
+        }
+    }
+
+    #[test]
+    fn test_trait_stats() {
+        let mut island = Island::build("WWWW\nWLLW\nWWWW", 1).unwrap();
+        island
+            .add_population(&[PopulationEntry::from(((1, 1), "Herbivore".to_string(), 2))])
+            .unwrap();
+        assert!(island.trait_stats().is_empty());
+
+        island.set_genetics(Some(genetics())).unwrap();
+        let fauna = island.cell_mut((1, 1)).unwrap().fauna.as_mut().unwrap();
+        fauna.get_mut(SpeciesId::HERBIVORE)[0].params.f = 8.0;
+        fauna.get_mut(SpeciesId::HERBIVORE)[1].params.f = 12.0;
+
+        let stats = island.trait_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(
+            stats[0],
+            TraitStats {
+                species: SpeciesId::HERBIVORE,
+                key: "f".to_string(),
+                mean: 10.0,
+                variance: 4.0,
+            }
+        );
+        assert_eq!(stats[1].key, "mu");
+        assert_eq!(stats[1].variance, 0.0);
+    }
+
+    #[test]
+    fn test_traits_vary_over_generations() {
+        let mut island = Island::build("WWWW\nWLLW\nWWWW", 1).unwrap();
+        island.set_genetics(Some(genetics())).unwrap();
+        island
+            .add_animals((1, 1), "Herbivore", Stats::from(5, 60.0), 50)
+            .unwrap();
+
+        for _ in 0..5 {
+            island.yearly_cycle();
+        }
+
+        let f = island
+            .trait_stats()
+            .into_iter()
+            .find(|stats| stats.key == "f")
+            .unwrap();
+        assert!(f.variance > 0.0, "{f:?}");
+    }
+
+    #[test]
+    fn test_set_parameters_keeps_evolved_traits() {
+        let mut island = Island::build("WWWW\nWLLW\nWWWW", 1).unwrap();
+        island.set_genetics(Some(genetics())).unwrap();
+        island
+            .add_population(&[PopulationEntry::from(((1, 1), "Herbivore".to_string(), 2))])
+            .unwrap();
+        let fauna = island.cell_mut((1, 1)).unwrap().fauna.as_mut().unwrap();
+        fauna.get_mut(SpeciesId::HERBIVORE)[0].params.f = 8.0;
+        fauna.get_mut(SpeciesId::HERBIVORE)[1].params.f = 12.0;
+
+        let partial = HashMap::from([("beta".to_string(), 0.8)]);
+        island
+            .set_animal_parameters(SpeciesId::HERBIVORE, &partial)
+            .unwrap();
+
+        let fauna = island.cell((1, 1)).unwrap().fauna.as_ref().unwrap();
+        let herbivores = fauna.get(SpeciesId::HERBIVORE);
+        assert_eq!(
+            (herbivores[0].params.f, herbivores[1].params.f),
+            (8.0, 12.0)
+        );
+        assert!(herbivores.iter().all(|herb| herb.params.beta == 0.8));
+        assert_eq!(island.species().params(SpeciesId::HERBIVORE).beta, 0.8);
+    }
+
+    #[test]
+    fn test_invalid_genetics() {
+        let mut island = Island::build("WWW\nWLW\nWWW", 1).unwrap();
+        let genetics = Genetics {
+            mutation: BTreeMap::from([("speed".to_string(), 1.0)]),
+        };
+
+        assert!(island.set_genetics(Some(genetics)).is_err());
+        assert!(island.genetics().is_none());
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use super::*;
+
+    const MAP: &str = "WWWWW\nWLLLW\nWLHLW\nWWWWW";
+
+    fn herbivores(island: &mut Island, loc: (u32, u32), count: u32) {
+        island
+            .add_population(&[PopulationEntry::from((loc, "Herbivore".to_string(), count))])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_fire() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        herbivores(&mut island, (1, 1), 100);
+        herbivores(&mut island, (3, 1), 10);
+        island
+            .set_events(vec![Event::Fire {
+                year: 1,
+                region: ((0, 0), (1, 2)),
+                kill: 0.5,
+            }])
+            .unwrap();
+
+        island.apply_events();
+
+        let record = &island.applied_events()[0];
+        assert_eq!((record.kind, record.cells), ("fire", 2));
+        assert!(record.killed > 20 && record.killed < 80, "{record:?}");
+        assert_eq!(island.count(SpeciesId::HERBIVORE), 110 - record.killed);
+        assert_eq!(island.cell((1, 1)).unwrap().fodder, 0.0);
+        assert_eq!(island.cell((3, 1)).unwrap().fodder, 800.0);
+    }
+
+    #[test]
+    fn test_flood_recedes() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        herbivores(&mut island, (1, 1), 20);
+        herbivores(&mut island, (2, 1), 10);
+        island
+            .set_events(vec![Event::Flood {
+                year: 1,
+                region: ((1, 1), (2, 2)),
+                duration: 2,
+                drown: 0.0,
+            }])
+            .unwrap();
+
+        island.yearly_cycle();
+
+        let record = &island.applied_events()[0];
+        assert_eq!((record.kind, record.cells), ("flood", 3));
+        // (1, 1) has no land left around it, the animals of (2, 1) flee
+        assert_eq!((record.killed, record.moved), (20, 10));
+        assert_eq!(island.cell((1, 1)).unwrap().get_cell(), CellType::Water);
+        assert_eq!(island.cell((2, 2)).unwrap().get_cell(), CellType::Highland);
+
+        island.yearly_cycle();
+        assert!(island.applied_events().is_empty());
+        island.yearly_cycle();
+
+        assert_eq!(island.applied_events()[0].kind, "flood receded");
+        assert_eq!(island.cell((1, 1)).unwrap().get_cell(), CellType::Lowland);
+        assert!(island.cell((1, 1)).unwrap().fauna.is_some());
+    }
+
+    #[test]
+    fn test_cull_takes_the_fittest() {
+        let mut island = Island::build(MAP, 1).unwrap();
+        island
+            .add_animals((1, 1), "Carnivore", Stats::from(5, 10.0), 5)
+            .unwrap();
+        island
+            .add_animals((3, 2), "Carnivore", Stats::from(5, 40.0), 3)
+            .unwrap();
+        island
+            .set_events(vec![Event::Cull {
+                year: 1,
+                species: "Carnivore".to_string(),
+                count: 4,
+                region: None,
+            }])
+            .unwrap();
+
+        island.apply_events();
+
+        let record = &island.applied_events()[0];
+        assert_eq!((record.cells, record.killed), (2, 4));
+        let fauna = island.cell((3, 2)).unwrap().fauna.as_ref().unwrap();
+        assert_eq!(fauna.count(SpeciesId::CARNIVORE), 0);
+        assert_eq!(island.count(SpeciesId::CARNIVORE), 4);
+
+        let cull = Event::Cull {
+            year: 1,
+            species: "Wolf".to_string(),
+            count: 1,
+            region: None,
+        };
+        assert!(island.set_events(vec![cull]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod terrain_tests {
+    use super::*;
+
+    const DYNAMICS: TerrainDynamics = TerrainDynamics {
+        degrade_after: 1,
+        recover_after: 2,
+        exhausted: 0.05,
+        low_pressure: 0.2,
+    };
+
+    #[test]
+    fn test_overgrazing_degrades() {
+        let mut island = Island::build("WWWW\nWLLW\nWWWW", 1).unwrap();
+        island.set_terrain(Some(DYNAMICS)).unwrap();
+        island
+            .add_animals((1, 1), "Herbivore", Stats::from(5, 30.0), 200)
+            .unwrap();
+
+        island.yearly_cycle();
+
+        assert_eq!(island.terrain_map(), vec!["WWWW", "WHLW", "WWWW"]);
+        assert_eq!(island.map_vec()[1], "WLLW");
+        assert!(island.cell((1, 1)).unwrap().fodder <= 300.0);
+    }
+
+    #[test]
+    fn test_rested_land_recovers() {
+        let mut island = Island::build("WWWW\nWLHW\nWWWW", 1).unwrap();
+        island.set_terrain(Some(DYNAMICS)).unwrap();
+        for loc in [(1, 1), (2, 1)] {
+            let landscape = island.landscape_params().clone();
+            island
+                .cell_mut(loc)
+                .unwrap()
+                .set_cell_type(CellType::Desert, &landscape);
+        }
+
+        island.yearly_cycle();
+        assert_eq!(island.terrain_map()[1], "WDDW");
+        island.yearly_cycle();
+        assert_eq!(island.terrain_map()[1], "WHHW");
+        island.yearly_cycle();
+        island.yearly_cycle();
+        assert_eq!(island.terrain_map()[1], "WLHW");
+    }
+
+    #[test]
+    fn test_grazed_desert_does_not_recover() {
+        let mut island = Island::build("WWW\nWLW\nWWW", 1).unwrap();
+        island.set_terrain(Some(DYNAMICS)).unwrap();
+        let landscape = island.landscape_params().clone();
+        island
+            .cell_mut((1, 1))
+            .unwrap()
+            .set_cell_type(CellType::Desert, &landscape);
+        island
+            .add_animals((1, 1), "Herbivore", Stats::from(5, 50.0), 50)
+            .unwrap();
+
+        for _ in 0..4 {
+            island.yearly_cycle();
+        }
+
+        assert!(island.get_pop()[0] > 0);
+        assert_eq!(island.terrain_map()[1], "WDW");
     }
 }
@@ -1,35 +1,54 @@
 pub mod animal;
 pub mod cell;
+pub mod climate;
+pub mod config;
+pub mod disease;
+pub mod error;
+pub mod events;
 pub mod island;
+pub mod migration;
+pub mod output;
 pub mod simulation;
+pub mod terrain;
 
-use island::Island;
-use std::error::Error;
+pub use config::Config;
+pub use error::BioSimError;
+use simulation::BioSim;
 
-pub struct Config {
-    island_map: String,
-    ini_pop: Vec<((u32, u32), String, u32)>,
+pub fn run(config: Config) -> Result<(), BioSimError> {
+    let mut sim = BioSim::from_config(&config)?;
+    run_simulation(&mut sim, &config)
 }
 
-impl Config {
-    pub fn build(
-        island_map: String,
-        ini_pop: Vec<((u32, u32), String, u32)>,
-    ) -> Result<Config, &'static str> {
-        Ok(Config {
-            island_map,
-            ini_pop,
-        })
+// simulates config.num_years on an already set up simulation, reporting as the output settings ask
+pub fn run_simulation(sim: &mut BioSim, config: &Config) -> Result<(), BioSimError> {
+    if let Some(dir) = &config.output.dir {
+        sim.log_to_csv(dir)?;
+    }
+
+    for _ in 0..config.num_years {
+        sim.simulate(1)?;
+
+        if let Some(interval) = config.output.print_interval {
+            if sim.year().is_multiple_of(interval) {
+                let counts: Vec<String> = sim
+                    .island()
+                    .get_pop()
+                    .iter()
+                    .map(usize::to_string)
+                    .collect();
+                println!("{}: {}", sim.year(), counts.join(", "));
+            }
+        }
     }
-}
-pub fn run(config: Config) -> Result<(), Box<dyn Error>> {
-    let _ = Island::build(&config.island_map)?;
 
     Ok(())
 }
 #[cfg(test)]
 mod lib_tests {
     use super::*;
+    use animal::SpeciesId;
+    use config::{AnimalEntry, PopulationEntry};
 
     #[test]
     fn config_init() {
@@ -39,8 +58,86 @@ mod lib_tests {
         let Config {
             island_map,
             ini_pop,
+            ..
         } = test_config;
 
-        assert_eq!((island_map, ini_pop), (input_map, input_pop));
+        let expected_pop = vec![PopulationEntry {
+            loc: (1, 1),
+            pop: vec![AnimalEntry {
+                species: "Herbivore".to_string(),
+                age: None,
+                weight: None,
+                sex: None,
+                infection: None,
+                count: 200,
+            }],
+        }];
+
+        assert_eq!((island_map, ini_pop), (input_map, expected_pop));
+    }
+
+    #[test]
+    fn run_scenario() {
+        let config = Config::from_toml_str(
+            r#"
+            seed = 3
+            num_years = 10
+            island_map = "WWWW\nWLHW\nWWWW"
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [{ species = "Herbivore", count = 20 }]
+
+            [animal_params.Herbivore]
+            beta = 0.8
+            "#,
+        )
+        .unwrap();
+
+        run(config).unwrap();
+    }
+
+    #[test]
+    fn scenario_with_age_and_weight() {
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "WWW\nWLW\nWWW"
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [{ species = "Carnivore", age = 2, weight = 9.0, count = 3 }]
+            "#,
+        )
+        .unwrap();
+
+        let sim = BioSim::from_config(&config).unwrap();
+        let carnivores = sim
+            .island()
+            .cell((1, 1))
+            .unwrap()
+            .fauna
+            .as_ref()
+            .unwrap()
+            .get(SpeciesId::CARNIVORE);
+
+        assert_eq!(carnivores.len(), 3);
+        assert!(carnivores
+            .iter()
+            .all(|carn| carn.stats.age == 2 && carn.stats.weight == 9.0));
+    }
+
+    #[test]
+    fn run_reports_typed_error() {
+        let config = Config::build(
+            "WWW\nWLW\nWWW".to_string(),
+            vec![((0, 1), "Herbivore".to_string(), 5)],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            run(config),
+            Err(BioSimError::AnimalsInWater((0, 1)))
+        ));
     }
 }
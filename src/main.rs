@@ -1,41 +1,175 @@
-use biosim_rust::Config;
-use std::process;
-
-pub mod animal;
-pub mod cell;
-pub mod island;
-pub mod simulation;
-
-use island::Island;
-
-fn main() {
-    let input_map = "
-        WWWW
-        WLLW
-        WLLW
-        WWWW"
-        .to_string();
-
-    let input_pop = vec![
-        ((1, 1), "Herbivore".to_string(), 80),
-        ((1, 1), "Carnivore".to_string(), 1),
-    ];
-
-    let mut island = Island::build(&input_map).unwrap();
-
-    for (coord, species, amount) in input_pop {
-        island
-            .map
-            .get_mut(&coord)
-            .unwrap()
-            .add_animal_from(species, amount)
-    }
-
-    let mut herb;
-    let mut carn;
-    for i in 0..100 {
-        (herb, carn) = island.get_pop();
-        println!("{i}: {herb}, {carn}");
-        island.yearly_cycle();
+use biosim_rust::{island::Island, simulation::BioSim, Config};
+use clap::{Parser, Subcommand};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+// exit code 2 is used by clap for invalid command line arguments
+const EXIT_RUN_FAILED: u8 = 1;
+const EXIT_INVALID_INPUT: u8 = 3;
+
+#[derive(Parser)]
+#[command(version, about = "Population dynamics on an island")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    /// Only print errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print the population every year
+    #[arg(short, long, global = true)]
+    verbose: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run a .toml or .json scenario file
+    Run {
+        scenario: PathBuf,
+
+        /// Override the seed of the scenario
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Override the number of years to simulate
+        #[arg(long)]
+        years: Option<u32>,
+
+        /// Directory to write the yearly csv population logs to
+        #[arg(long)]
+        output_dir: Option<PathBuf>,
+    },
+    /// Check that a scenario file can be loaded, without running it
+    Validate { scenario: PathBuf },
+    /// Print an island map file
+    RenderMap {
+        map: PathBuf,
+
+        /// Surround the map with water if its border is not all water
+        #[arg(long)]
+        pad: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Run {
+            ref scenario,
+            seed,
+            years,
+            ref output_dir,
+        } => run(&cli, scenario, seed, years, output_dir.clone()),
+        Command::Validate { ref scenario } => validate(&cli, scenario),
+        Command::RenderMap { ref map, pad } => render_map(map, pad),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err((code, err)) => {
+            eprintln!("error: {err}");
+            ExitCode::from(code)
+        }
+    }
+}
+
+type CliResult = Result<(), (u8, String)>;
+
+fn load_config(scenario: &Path) -> Result<Config, (u8, String)> {
+    Config::from_file(scenario).map_err(|err| (EXIT_INVALID_INPUT, err.to_string()))
+}
+
+fn build_sim(scenario: &Path, config: &Config) -> Result<BioSim, (u8, String)> {
+    BioSim::from_config(config)
+        .map_err(|err| (EXIT_INVALID_INPUT, format!("{}: {err}", scenario.display())))
+}
+
+fn run(
+    cli: &Cli,
+    scenario: &Path,
+    seed: Option<u64>,
+    years: Option<u32>,
+    output_dir: Option<PathBuf>,
+) -> CliResult {
+    let mut config = load_config(scenario)?;
+
+    if let Some(seed) = seed {
+        config.seed = seed;
+    }
+    if let Some(years) = years {
+        config.num_years = years;
+    }
+    if output_dir.is_some() {
+        config.output.dir = output_dir;
+    }
+    if cli.verbose {
+        config.output.print_interval = Some(1);
+    }
+    if cli.quiet {
+        config.output.print_interval = None;
     }
+
+    let mut sim = build_sim(scenario, &config)?;
+    biosim_rust::run_simulation(&mut sim, &config)
+        .map_err(|err| (EXIT_RUN_FAILED, err.to_string()))?;
+
+    if !cli.quiet {
+        let counts: Vec<String> = sim
+            .island()
+            .species()
+            .iter()
+            .zip(sim.island().get_pop())
+            .map(|((_, info), count)| format!("{count} {}", info.display_name))
+            .collect();
+        println!("Year {}: {}", sim.year(), counts.join(", "));
+    }
+
+    Ok(())
+}
+
+fn validate(cli: &Cli, scenario: &Path) -> CliResult {
+    let config = load_config(scenario)?;
+    let sim = build_sim(scenario, &config)?;
+
+    if !cli.quiet {
+        println!(
+            "{}: ok, {}x{} map, {} animals, {} years",
+            scenario.display(),
+            sim.island().width(),
+            sim.island().height(),
+            sim.num_animals(),
+            config.num_years
+        );
+    }
+
+    Ok(())
+}
+
+fn render_map(map: &Path, pad: bool) -> CliResult {
+    let raw_map = fs::read_to_string(map).map_err(|err| {
+        (
+            EXIT_INVALID_INPUT,
+            format!("Could not read {}: {err}", map.display()),
+        )
+    })?;
+    let invalid = |err| (EXIT_INVALID_INPUT, format!("{}: {err}", map.display()));
+
+    let padded_map = if pad {
+        Island::pad_with_water(&raw_map).map_err(invalid)?
+    } else {
+        None
+    };
+    let island = Island::build(padded_map.as_deref().unwrap_or(&raw_map), 0).map_err(invalid)?;
+
+    for line in island.map_vec() {
+        let cells: Vec<String> = line.chars().map(String::from).collect();
+        println!("{}", cells.join(" "));
+    }
+
+    Ok(())
 }
@@ -0,0 +1,588 @@
+use crate::animal::{Animal, Diet, SpeciesId, SpeciesRegistry};
+use crate::cell::{Cell, CellType};
+use crate::error::BioSimError;
+use rand::{prelude::SliceRandom, Rng, RngCore};
+use rand_distr::{Distribution, Exp, Pareto};
+use serde::Deserialize;
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
+// decides where an animal that leaves its cell heads for. A destination in the
+// water or off the map makes the animal stay where it is.
+pub trait MigrationStrategy: fmt::Debug + Send + Sync {
+    fn destination(
+        &self,
+        animal: &Animal,
+        from: (u32, u32),
+        surroundings: &Surroundings,
+        rng: &mut dyn RngCore,
+    ) -> Option<(u32, u32)>;
+
+    // whether destination reads the surroundings, which are only built when some
+    // species needs them
+    fn needs_surroundings(&self) -> bool {
+        false
+    }
+}
+
+// a cell of the neighbourhood, uniformly at random
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomWalk {
+    pub neighbourhood: Neighbourhood,
+}
+
+impl MigrationStrategy for RandomWalk {
+    fn destination(
+        &self,
+        _animal: &Animal,
+        from: (u32, u32),
+        _surroundings: &Surroundings,
+        rng: &mut dyn RngCore,
+    ) -> Option<(u32, u32)> {
+        Cell::get_random_cell_in(from, &self.neighbourhood.offsets(), rng)
+    }
+}
+
+// a cell of the neighbourhood with probability proportional to the food there per
+// animal of the species, counting the migrant itself. Food is the fodder for grazers
+// and the biomass of the prey species for hunters, omnivores count both. Without any
+// food around the animal falls back to a random walk.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FoodGuided {
+    pub neighbourhood: Neighbourhood,
+}
+
+impl MigrationStrategy for FoodGuided {
+    fn destination(
+        &self,
+        animal: &Animal,
+        from: (u32, u32),
+        surroundings: &Surroundings,
+        rng: &mut dyn RngCore,
+    ) -> Option<(u32, u32)> {
+        let species = animal.species;
+        let food_per_animal = |loc: (u32, u32)| {
+            let (Some(view), Some(diet)) = (surroundings.cell(loc), surroundings.diet(species))
+            else {
+                return 0.0;
+            };
+            if !view.land {
+                return 0.0;
+            }
+
+            let fodder = if diet.fodder { view.fodder } else { 0.0 };
+            let prey: f32 = diet.prey.iter().map(|prey| view.biomass(*prey)).sum();
+            (fodder + prey) / (view.count(species) + 1) as f32
+        };
+
+        let options: Vec<((u32, u32), f32)> = self
+            .neighbourhood
+            .offsets()
+            .iter()
+            .filter_map(|&(dx, dy)| {
+                let loc = (
+                    from.0.checked_add_signed(dx)?,
+                    from.1.checked_add_signed(dy)?,
+                );
+                Some((loc, food_per_animal(loc)))
+            })
+            .filter(|&(_, food)| food > 0.0)
+            .collect();
+
+        match options.choose_weighted(rng, |&(_, food)| food) {
+            Ok(&(loc, _)) => Some(loc),
+            Err(_) => RandomWalk {
+                neighbourhood: self.neighbourhood,
+            }
+            .destination(animal, from, surroundings, rng),
+        }
+    }
+
+    fn needs_surroundings(&self) -> bool {
+        true
+    }
+}
+
+// a jump in a uniformly random direction over a distance drawn from the kernel,
+// straight over any water in between
+#[derive(Debug, Clone, Copy)]
+pub struct Dispersal {
+    pub kernel: Kernel,
+}
+
+impl MigrationStrategy for Dispersal {
+    fn destination(
+        &self,
+        _animal: &Animal,
+        from: (u32, u32),
+        _surroundings: &Surroundings,
+        rng: &mut dyn RngCore,
+    ) -> Option<(u32, u32)> {
+        let distance = self.kernel.distance(rng);
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let dx = (distance * angle.cos()).round() as i32;
+        let dy = (distance * angle.sin()).round() as i32;
+
+        Some((
+            from.0.checked_add_signed(dx)?,
+            from.1.checked_add_signed(dy)?,
+        ))
+    }
+}
+
+// the cells an animal can reach in one step:
+//
+// neighbourhood = "von_neumann"   the four cells sharing a side, the default
+// neighbourhood = "moore"         the eight cells sharing a side or a corner
+// neighbourhood = { radius = 2 }  every cell whose centre is at most radius away
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Neighbourhood {
+    #[default]
+    VonNeumann,
+    Moore,
+    Radius(u32),
+}
+
+// the same order as Cell::get_random_neighboring_cell always used
+const VON_NEUMANN: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const MOORE: [(i32, i32); 8] = [
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+];
+
+impl Neighbourhood {
+    pub fn offsets(&self) -> Cow<'static, [(i32, i32)]> {
+        match *self {
+            Neighbourhood::VonNeumann => Cow::Borrowed(&VON_NEUMANN),
+            Neighbourhood::Moore => Cow::Borrowed(&MOORE),
+            Neighbourhood::Radius(radius) => {
+                let r = radius as i32;
+                let offsets = (-r..=r)
+                    .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                    .filter(|&(dx, dy)| (dx, dy) != (0, 0) && dx * dx + dy * dy <= r * r)
+                    .collect();
+                Cow::Owned(offsets)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        match *self {
+            Neighbourhood::Radius(0) => Err(BioSimError::InvalidParameter {
+                key: "radius".to_string(),
+                value: 0.0,
+                reason: "must be at least 1",
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+// the distribution of jump distances in cells:
+//
+// dispersal = { kernel = "exponential", mean = 2.0 }
+// dispersal = { kernel = "levy", exponent = 2.0 }   p(d) ~ d^-exponent for d >= 1
+//
+// The Lévy kernel is heavy tailed, most jumps are short but a few cross the island.
+#[derive(PartialEq, Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "kernel", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Kernel {
+    Exponential { mean: f32 },
+    Levy { exponent: f32 },
+}
+
+impl Kernel {
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        let (key, value, valid, reason) = match *self {
+            Kernel::Exponential { mean } => (
+                "mean",
+                mean,
+                mean.is_finite() && mean > 0.0,
+                "must be a positive number",
+            ),
+            Kernel::Levy { exponent } => (
+                "exponent",
+                exponent,
+                exponent > 1.0 && exponent <= 3.0,
+                "must be above 1 and at most 3",
+            ),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(BioSimError::InvalidParameter {
+                key: key.to_string(),
+                value,
+                reason,
+            })
+        }
+    }
+
+    // panics on a kernel that does not validate
+    pub fn distance(&self, rng: &mut dyn RngCore) -> f32 {
+        match *self {
+            Kernel::Exponential { mean } => Exp::new(1.0 / mean).unwrap().sample(rng),
+            Kernel::Levy { exponent } => Pareto::new(1.0, exponent - 1.0).unwrap().sample(rng),
+        }
+    }
+}
+
+// how far the animals of a species get, in a scenario file:
+//
+// [movement.Herbivore]
+// neighbourhood = "moore"
+//
+// [movement.Carnivore]
+// dispersal = { kernel = "levy", exponent = 2.0 }
+//
+// With a dispersal kernel a migrant jumps instead of stepping to a neighbour, so it
+// can neither be combined with a neighbourhood nor with guided migration.
+#[derive(PartialEq, Debug, Clone, Copy, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Movement {
+    #[serde(default)]
+    pub neighbourhood: Neighbourhood,
+    pub dispersal: Option<Kernel>,
+}
+
+impl Movement {
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        self.neighbourhood.validate()?;
+        match self.dispersal {
+            Some(_) if self.neighbourhood != Neighbourhood::VonNeumann => {
+                Err(BioSimError::Scenario(
+                    "A dispersal kernel cannot be combined with a neighbourhood".to_string(),
+                ))
+            }
+            Some(kernel) => kernel.validate(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn strategy(&self, mode: MigrationMode) -> Result<Arc<dyn MigrationStrategy>, BioSimError> {
+        self.validate()?;
+
+        let neighbourhood = self.neighbourhood;
+        match (mode, self.dispersal) {
+            (MigrationMode::Random, None) => Ok(Arc::new(RandomWalk { neighbourhood })),
+            (MigrationMode::Guided, None) => Ok(Arc::new(FoodGuided { neighbourhood })),
+            (MigrationMode::Random, Some(kernel)) => Ok(Arc::new(Dispersal { kernel })),
+            (MigrationMode::Guided, Some(_)) => Err(BioSimError::Scenario(
+                "Guided migration cannot be combined with a dispersal kernel".to_string(),
+            )),
+        }
+    }
+}
+
+// the strategies that can be chosen in a scenario file:
+//
+// [migration]
+// Herbivore = "guided"
+// Carnivore = "random"
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationMode {
+    Random,
+    Guided,
+}
+
+impl MigrationMode {
+    // the strategy with the von Neumann neighbourhood, see Movement for the others
+    pub fn strategy(&self) -> Arc<dyn MigrationStrategy> {
+        match self {
+            MigrationMode::Random => Arc::new(RandomWalk::default()),
+            MigrationMode::Guided => Arc::new(FoodGuided::default()),
+        }
+    }
+}
+
+const RANDOM_WALK: RandomWalk = RandomWalk {
+    neighbourhood: Neighbourhood::VonNeumann,
+};
+
+// the strategy of every species, a random walk unless set otherwise
+#[derive(Debug, Clone, Default)]
+pub struct MigrationStrategies {
+    strategies: BTreeMap<SpeciesId, Arc<dyn MigrationStrategy>>,
+}
+
+impl MigrationStrategies {
+    pub fn get(&self, species: SpeciesId) -> &dyn MigrationStrategy {
+        self.strategies
+            .get(&species)
+            .map_or(&RANDOM_WALK, |strategy| strategy.as_ref())
+    }
+
+    pub fn set(&mut self, species: SpeciesId, strategy: Arc<dyn MigrationStrategy>) {
+        self.strategies.insert(species, strategy);
+    }
+
+    pub fn needs_surroundings(&self) -> bool {
+        self.strategies
+            .values()
+            .any(|strategy| strategy.needs_surroundings())
+    }
+}
+
+// what animals go by when they pick a destination: the island as it was at the
+// start of the yearly cycle, before any cell fed or migrated
+#[derive(Debug, Clone, Default)]
+pub struct Surroundings {
+    width: usize,
+    cells: Vec<CellView>,
+    diets: Vec<Diet>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CellView {
+    pub land: bool,
+    pub fodder: f32,
+    // indexed by SpeciesId
+    counts: Vec<usize>,
+    biomass: Vec<f32>,
+}
+
+impl CellView {
+    pub fn count(&self, species: SpeciesId) -> usize {
+        self.counts.get(species.index()).copied().unwrap_or(0)
+    }
+
+    pub fn biomass(&self, species: SpeciesId) -> f32 {
+        self.biomass.get(species.index()).copied().unwrap_or(0.0)
+    }
+}
+
+impl Surroundings {
+    // cells is the row-major grid of an island of the given width
+    pub fn new(cells: &[Cell], width: usize, species: &SpeciesRegistry) -> Surroundings {
+        let number_of_species = species.ids().count();
+        let views = cells
+            .iter()
+            .map(|cell| {
+                let mut view = CellView {
+                    land: cell.get_cell() != CellType::Water,
+                    fodder: cell.fodder,
+                    counts: vec![0; number_of_species],
+                    biomass: vec![0.0; number_of_species],
+                };
+                for animal in cell.fauna.iter().flat_map(|fauna| fauna.animals()) {
+                    view.counts[animal.species.index()] += 1;
+                    view.biomass[animal.species.index()] += animal.stats.weight;
+                }
+                view
+            })
+            .collect();
+
+        Surroundings {
+            width,
+            cells: views,
+            diets: species.iter().map(|(_, info)| info.diet.clone()).collect(),
+        }
+    }
+
+    pub fn cell(&self, (x, y): (u32, u32)) -> Option<&CellView> {
+        if x as usize >= self.width {
+            return None;
+        }
+        self.cells.get(y as usize * self.width + x as usize)
+    }
+
+    pub fn diet(&self, species: SpeciesId) -> Option<&Diet> {
+        self.diets.get(species.index())
+    }
+}
+
+#[cfg(test)]
+mod migration_tests {
+    use super::*;
+    use crate::animal::Stats;
+    use crate::cell::LandscapeParameters;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    // a row of land between water, the herbivores are in the middle cell
+    fn row_of_cells(left: CellType, right: CellType) -> Surroundings {
+        let registry = SpeciesRegistry::default();
+        let landscape = LandscapeParameters::default();
+        let mut cells: Vec<Cell> = (0..3)
+            .flat_map(|y| (0..5).map(move |x| (x, y)))
+            .map(|loc| Cell::new(CellType::Water, loc, &landscape))
+            .collect();
+        cells[6] = Cell::new(left, (1, 1), &landscape);
+        cells[7] = Cell::new(CellType::Lowland, (2, 1), &landscape);
+        cells[8] = Cell::new(right, (3, 1), &landscape);
+        cells[7]
+            .add_animals("Herbivore", Stats::new_default(), 10, &registry)
+            .unwrap();
+
+        Surroundings::new(&cells, 5, &registry)
+    }
+
+    fn pick_destinations(
+        strategy: &dyn MigrationStrategy,
+        animal: &Animal,
+        surroundings: &Surroundings,
+    ) -> Vec<(u32, u32)> {
+        let mut rng = StdRng::seed_from_u64(3);
+        (0..200)
+            .filter_map(|_| strategy.destination(animal, (2, 1), surroundings, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn test_surroundings() {
+        let surroundings = row_of_cells(CellType::Highland, CellType::Desert);
+
+        let view = surroundings.cell((2, 1)).unwrap();
+        assert_eq!(view.count(SpeciesId::HERBIVORE), 10);
+        assert_eq!(view.biomass(SpeciesId::HERBIVORE), 200.0);
+        assert_eq!(surroundings.cell((1, 1)).unwrap().fodder, 300.0);
+        assert!(!surroundings.cell((2, 0)).unwrap().land);
+        assert!(surroundings.cell((5, 1)).is_none());
+    }
+
+    #[test]
+    fn test_herbivores_follow_fodder() {
+        let surroundings = row_of_cells(CellType::Highland, CellType::Desert);
+        let destinations =
+            pick_destinations(&FoodGuided::default(), &Animal::herbivore(), &surroundings);
+
+        assert_eq!(destinations.len(), 200);
+        assert!(destinations.iter().all(|&loc| loc == (1, 1)));
+
+        let surroundings = row_of_cells(CellType::Highland, CellType::Lowland);
+        let destinations =
+            pick_destinations(&FoodGuided::default(), &Animal::herbivore(), &surroundings);
+        let to_lowland = destinations.iter().filter(|&&loc| loc == (3, 1)).count();
+        assert!(to_lowland > 120 && to_lowland < 190, "{to_lowland}");
+    }
+
+    #[test]
+    fn test_carnivores_follow_prey() {
+        let surroundings = row_of_cells(CellType::Highland, CellType::Lowland);
+        let destinations =
+            pick_destinations(&FoodGuided::default(), &Animal::carnivore(), &surroundings);
+
+        // no herbivores next to them, so the carnivores walk at random
+        let directions: Vec<_> = [(2, 0), (3, 1), (2, 2), (1, 1)]
+            .into_iter()
+            .filter(|loc| destinations.contains(loc))
+            .collect();
+        assert_eq!(directions.len(), 4);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let from = (3, 1);
+        for _ in 0..50 {
+            let destination = FoodGuided::default().destination(
+                &Animal::carnivore(),
+                from,
+                &surroundings,
+                &mut rng,
+            );
+            assert_eq!(destination, Some((2, 1)));
+        }
+    }
+
+    #[test]
+    fn test_neighbourhood_offsets() {
+        assert_eq!(Neighbourhood::VonNeumann.offsets().len(), 4);
+        assert_eq!(Neighbourhood::Moore.offsets().len(), 8);
+        assert_eq!(Neighbourhood::Radius(1).offsets().len(), 4);
+        assert_eq!(Neighbourhood::Radius(2).offsets().len(), 12);
+        assert!(Neighbourhood::Radius(0).validate().is_err());
+
+        let surroundings = row_of_cells(CellType::Highland, CellType::Lowland);
+        let walk = RandomWalk {
+            neighbourhood: Neighbourhood::Moore,
+        };
+        let destinations = pick_destinations(&walk, &Animal::herbivore(), &surroundings);
+        assert!(destinations.contains(&(1, 0)) && destinations.contains(&(3, 2)));
+        assert!(destinations
+            .iter()
+            .all(|&(x, y)| x.abs_diff(2) <= 1 && y.abs_diff(1) <= 1));
+    }
+
+    #[test]
+    fn test_dispersal_kernels() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let exponential = Kernel::Exponential { mean: 4.0 };
+        let mean = (0..2000)
+            .map(|_| exponential.distance(&mut rng))
+            .sum::<f32>()
+            / 2000.0;
+        assert!((mean - 4.0).abs() < 0.5, "{mean}");
+
+        let levy = Kernel::Levy { exponent: 2.0 };
+        let distances: Vec<f32> = (0..2000).map(|_| levy.distance(&mut rng)).collect();
+        assert!(distances.iter().all(|&d| d >= 1.0));
+        assert!(distances.iter().any(|&d| d > 50.0));
+
+        assert!(Kernel::Exponential { mean: 0.0 }.validate().is_err());
+        assert!(Kernel::Levy { exponent: 1.0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_dispersal_jumps() {
+        let surroundings = Surroundings::default();
+        let dispersal = Dispersal {
+            kernel: Kernel::Exponential { mean: 10.0 },
+        };
+        let mut rng = StdRng::seed_from_u64(3);
+        let from = (50, 50);
+        let jumps: Vec<u32> = (0..500)
+            .filter_map(|_| {
+                dispersal.destination(&Animal::herbivore(), from, &surroundings, &mut rng)
+            })
+            .map(|(x, y)| x.abs_diff(from.0).max(y.abs_diff(from.1)))
+            .collect();
+
+        assert_eq!(jumps.len(), 500);
+        assert!(jumps.iter().any(|&d| d > 10));
+    }
+
+    #[test]
+    fn test_movement_strategy() {
+        let movement = Movement {
+            neighbourhood: Neighbourhood::Moore,
+            dispersal: None,
+        };
+        let strategy = movement.strategy(MigrationMode::Guided).unwrap();
+        assert!(format!("{strategy:?}").contains("Moore"));
+
+        let movement = Movement {
+            neighbourhood: Neighbourhood::VonNeumann,
+            dispersal: Some(Kernel::Levy { exponent: 2.0 }),
+        };
+        assert!(
+            format!("{:?}", movement.strategy(MigrationMode::Random).unwrap()).contains("Levy")
+        );
+        assert!(movement.strategy(MigrationMode::Guided).is_err());
+
+        let movement = Movement {
+            neighbourhood: Neighbourhood::Moore,
+            ..movement
+        };
+        assert!(movement.strategy(MigrationMode::Random).is_err());
+    }
+
+    #[test]
+    fn test_strategies_default_to_random_walk() {
+        let mut strategies = MigrationStrategies::default();
+        assert!(!strategies.needs_surroundings());
+        assert!(format!("{:?}", strategies.get(SpeciesId::HERBIVORE)).contains("RandomWalk"));
+
+        strategies.set(SpeciesId::HERBIVORE, MigrationMode::Random.strategy());
+        assert!(!strategies.needs_surroundings());
+
+        strategies.set(SpeciesId::HERBIVORE, MigrationMode::Guided.strategy());
+        assert!(strategies.needs_surroundings());
+        assert!(format!("{:?}", strategies.get(SpeciesId::HERBIVORE)).contains("FoodGuided"));
+        assert!(format!("{:?}", strategies.get(SpeciesId::CARNIVORE)).contains("RandomWalk"));
+    }
+}
@@ -0,0 +1,213 @@
+use crate::error::BioSimError;
+use crate::events::Event;
+use crate::island::Island;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+pub const TOTALS_FILE: &str = "population.csv";
+pub const CELLS_FILE: &str = "population_cells.csv";
+pub const TRAITS_FILE: &str = "traits.csv";
+pub const CLIMATE_FILE: &str = "climate.csv";
+pub const DISEASE_FILE: &str = "disease.csv";
+pub const DISEASE_CELLS_FILE: &str = "disease_cells.csv";
+pub const EVENTS_FILE: &str = "events.csv";
+pub const TERRAIN_FILE: &str = "terrain.csv";
+
+// yearly population log as csv files:
+//   population.csv:       year,Herbivore,Carnivore,... (one column per species)
+//   population_cells.csv: year,x,y,species,count (cells without a species are left out)
+//   traits.csv:           year,species,trait,mean,variance (only with genetics)
+//   climate.csv:          year,event (the active climate events, only with a schedule)
+//   disease.csv:          year,species,susceptible,infected,recovered (only with a disease)
+//   disease_cells.csv:    year,x,y,species,susceptible,infected,recovered
+//   events.csv:           year,event,cells,killed,moved (the applied events, only with a schedule)
+//   terrain.csv:          year,y,cells (the current map, one line per row, only when cell
+//                         types can change through terrain dynamics or floods)
+// The files are flushed after every year so a running simulation can be followed.
+// Locations refer to the map as written: with a padded map the offset of the added
+// water border is taken off x and y, and terrain.csv leaves out the border.
+#[derive(Debug)]
+pub struct CsvLog {
+    // the species columns of population.csv
+    species: Vec<String>,
+    offset: u32,
+    totals: BufWriter<File>,
+    cells: BufWriter<File>,
+    traits: Option<BufWriter<File>>,
+    climate: Option<BufWriter<File>>,
+    // totals and per cell counts
+    disease: Option<(BufWriter<File>, BufWriter<File>)>,
+    events: Option<BufWriter<File>>,
+    terrain: Option<BufWriter<File>>,
+}
+
+impl CsvLog {
+    // the columns are the species registered when the log is created, writing a year
+    // after the registry changed fails
+    pub fn create(dir: &Path, island: &Island, offset: u32) -> Result<CsvLog, BioSimError> {
+        fs::create_dir_all(dir)?;
+
+        let mut totals = BufWriter::new(File::create(dir.join(TOTALS_FILE))?);
+        let mut cells = BufWriter::new(File::create(dir.join(CELLS_FILE))?);
+
+        let species: Vec<String> = island
+            .species()
+            .iter()
+            .map(|(_, info)| info.name.clone())
+            .collect();
+        writeln!(totals, "year,{}", species.join(","))?;
+        writeln!(cells, "year,x,y,species,count")?;
+
+        let traits = match island.genetics() {
+            Some(_) => {
+                let mut traits = BufWriter::new(File::create(dir.join(TRAITS_FILE))?);
+                writeln!(traits, "year,species,trait,mean,variance")?;
+                Some(traits)
+            }
+            None => None,
+        };
+
+        let climate = if island.climate().is_empty() {
+            None
+        } else {
+            let mut climate = BufWriter::new(File::create(dir.join(CLIMATE_FILE))?);
+            writeln!(climate, "year,event")?;
+            Some(climate)
+        };
+
+        let disease = if island.disease().is_empty() {
+            None
+        } else {
+            let mut totals = BufWriter::new(File::create(dir.join(DISEASE_FILE))?);
+            let mut cells = BufWriter::new(File::create(dir.join(DISEASE_CELLS_FILE))?);
+            writeln!(totals, "year,species,susceptible,infected,recovered")?;
+            writeln!(cells, "year,x,y,species,susceptible,infected,recovered")?;
+            Some((totals, cells))
+        };
+
+        let events = if island.events().is_empty() {
+            None
+        } else {
+            let mut events = BufWriter::new(File::create(dir.join(EVENTS_FILE))?);
+            writeln!(events, "year,event,cells,killed,moved")?;
+            Some(events)
+        };
+
+        let floods = island
+            .events()
+            .iter()
+            .any(|event| matches!(event, Event::Flood { .. }));
+        let terrain = if island.terrain().is_some() || floods {
+            let mut terrain = BufWriter::new(File::create(dir.join(TERRAIN_FILE))?);
+            writeln!(terrain, "year,y,cells")?;
+            Some(terrain)
+        } else {
+            None
+        };
+
+        Ok(CsvLog {
+            species,
+            offset,
+            totals,
+            cells,
+            traits,
+            climate,
+            disease,
+            events,
+            terrain,
+        })
+    }
+
+    pub fn write_year(&mut self, year: u32, island: &Island) -> Result<(), BioSimError> {
+        let species = island.species().iter().map(|(_, info)| info.name.as_str());
+        if !species.eq(self.species.iter().map(String::as_str)) {
+            return Err(BioSimError::Scenario(format!(
+                "The species changed after the csv log was created with {}",
+                self.species.join(", ")
+            )));
+        }
+        let offset = self.offset;
+
+        let counts: Vec<String> = island.get_pop().iter().map(usize::to_string).collect();
+        writeln!(self.totals, "{year},{}", counts.join(","))?;
+
+        for ((x, y), counts) in island.get_pop_per_cell() {
+            let (x, y) = (x - offset, y - offset);
+            for ((_, info), count) in island.species().iter().zip(counts) {
+                if count > 0 {
+                    writeln!(self.cells, "{year},{x},{y},{},{count}", info.name)?;
+                }
+            }
+        }
+
+        if let Some(traits) = self.traits.as_mut() {
+            for stats in island.trait_stats() {
+                let name = &island.species().get(stats.species).name;
+                writeln!(
+                    traits,
+                    "{year},{name},{},{},{}",
+                    stats.key, stats.mean, stats.variance
+                )?;
+            }
+            traits.flush()?;
+        }
+
+        if let Some(climate) = self.climate.as_mut() {
+            for event in island.active_climate(year) {
+                writeln!(climate, "{year},{}", event.name)?;
+            }
+            climate.flush()?;
+        }
+
+        if let Some((totals, cells)) = self.disease.as_mut() {
+            for (species, counts) in island.infection_counts() {
+                let name = &island.species().get(species).name;
+                writeln!(
+                    totals,
+                    "{year},{name},{},{},{}",
+                    counts.susceptible, counts.infected, counts.recovered
+                )?;
+            }
+            for ((x, y), species, counts) in island.infection_counts_per_cell() {
+                let (x, y) = (x - offset, y - offset);
+                let name = &island.species().get(species).name;
+                writeln!(
+                    cells,
+                    "{year},{x},{y},{name},{},{},{}",
+                    counts.susceptible, counts.infected, counts.recovered
+                )?;
+            }
+            totals.flush()?;
+            cells.flush()?;
+        }
+
+        if let Some(events) = self.events.as_mut() {
+            let applied = island.applied_events().iter();
+            for record in applied.filter(|record| record.year == year) {
+                writeln!(
+                    events,
+                    "{year},{},{},{},{}",
+                    record.kind, record.cells, record.killed, record.moved
+                )?;
+            }
+            events.flush()?;
+        }
+
+        if let Some(terrain) = self.terrain.as_mut() {
+            let map = island.terrain_map();
+            let border = offset as usize;
+            for (y, row) in map[border..map.len() - border].iter().enumerate() {
+                writeln!(terrain, "{year},{y},{}", &row[border..row.len() - border])?;
+            }
+            terrain.flush()?;
+        }
+
+        self.totals.flush()?;
+        self.cells.flush()?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,715 @@
+use crate::animal::{Genetics, Reproduction, SpeciesEntry, SpeciesId};
+use crate::cell::{CellType, Regrowth};
+use crate::climate::ClimateEvent;
+use crate::config::Config;
+use crate::disease::DiseaseParams;
+use crate::error::BioSimError;
+use crate::events::Event;
+use crate::island::{Island, PopulationEntry};
+use crate::migration::{MigrationMode, MigrationStrategy};
+use crate::output::CsvLog;
+use crate::terrain::TerrainDynamics;
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+    sync::Arc,
+};
+
+// example of ini_pop: vec![((1, 1), "Herbivore".to_string(), 200)]
+pub type InitialPopulation = Vec<((u32, u32), String, u32)>;
+
+#[derive(Debug)]
+pub struct BioSim {
+    island: Island,
+    seed: u64,
+    // the water border added by padding the map, taken off the logged locations
+    offset: u32,
+    log: Option<CsvLog>,
+}
+
+impl BioSim {
+    pub fn new(
+        island_map: &str,
+        ini_pop: InitialPopulation,
+        seed: u64,
+    ) -> Result<BioSim, BioSimError> {
+        let island = Island::build(island_map, seed)?;
+
+        let mut sim = BioSim {
+            island,
+            seed,
+            offset: 0,
+            log: None,
+        };
+        let ini_pop: Vec<PopulationEntry> =
+            ini_pop.into_iter().map(PopulationEntry::from).collect();
+        sim.add_population(&ini_pop)?;
+
+        Ok(sim)
+    }
+
+    // sets up the island, parameters and population described by a scenario
+    pub fn from_config(config: &Config) -> Result<BioSim, BioSimError> {
+        let padded_map = if config.pad_map {
+            Island::pad_with_water(&config.island_map)?
+        } else {
+            None
+        };
+        // padding shifts every cell one step right and down
+        let offset = if padded_map.is_some() { 1 } else { 0 };
+        let island_map = padded_map.as_deref().unwrap_or(&config.island_map);
+
+        let mut sim = BioSim::new(island_map, Vec::new(), config.seed)?;
+        sim.offset = offset;
+
+        for entry in &config.species {
+            sim.register_species(entry)?;
+        }
+
+        for (name, partial_params) in &config.animal_params {
+            let species = sim.island.species().id(name)?;
+            sim.set_animal_parameters(species, partial_params)?;
+        }
+
+        for (name, &disease) in &config.disease {
+            let species = sim.island.species().id(name)?;
+            sim.set_disease(species, disease)?;
+        }
+
+        let moving = config.migration.keys().chain(config.movement.keys());
+        for name in moving.collect::<BTreeSet<_>>() {
+            let species = sim.island.species().id(name)?;
+            let mode = config
+                .migration
+                .get(name)
+                .copied()
+                .unwrap_or(MigrationMode::Random);
+            let movement = config.movement.get(name).copied().unwrap_or_default();
+            sim.set_migration_strategy(species, movement.strategy(mode)?);
+        }
+
+        sim.set_genetics(config.genetics.clone())?;
+        sim.set_terrain(config.terrain)?;
+        sim.set_reproduction(config.reproduction);
+
+        for (&cell_type, &f_max) in &config.landscape_params {
+            sim.set_landscape_parameters(cell_type, f_max)?;
+        }
+        for (&cell_type, &regrowth) in &config.regrowth {
+            sim.set_regrowth(cell_type, regrowth)?;
+        }
+
+        // regions refer to the map as written, like population locations
+        let shift = |(x, y): (u32, u32)| (x + offset, y + offset);
+        let climate = config
+            .climate
+            .iter()
+            .map(|event| ClimateEvent {
+                region: event.region.map(|(from, to)| (shift(from), shift(to))),
+                ..event.clone()
+            })
+            .collect();
+        sim.set_climate(climate)?;
+
+        let events = config
+            .events
+            .iter()
+            .map(|event| {
+                let mut event = event.clone();
+                if let Some(region) = event.region_mut() {
+                    *region = (shift(region.0), shift(region.1));
+                }
+                event
+            })
+            .collect();
+        sim.set_events(events)?;
+
+        // population locations refer to the map as written
+        let ini_pop: Vec<PopulationEntry> = config
+            .ini_pop
+            .iter()
+            .map(|entry| PopulationEntry {
+                loc: shift(entry.loc),
+                pop: entry.pop.clone(),
+            })
+            .collect();
+        sim.add_population(&ini_pop)?;
+
+        Ok(sim)
+    }
+
+    // introduce animals, for example carnivores after some years of herbivores only
+    pub fn add_population(&mut self, population: &[PopulationEntry]) -> Result<(), BioSimError> {
+        self.island.add_population(population)
+    }
+
+    // adds a species that can then be placed with add_population
+    pub fn register_species(&mut self, entry: &SpeciesEntry) -> Result<SpeciesId, BioSimError> {
+        self.island.register_species(entry)
+    }
+
+    pub fn set_animal_parameters(
+        &mut self,
+        species: SpeciesId,
+        partial_params: &HashMap<String, f32>,
+    ) -> Result<(), BioSimError> {
+        self.island.set_animal_parameters(species, partial_params)
+    }
+
+    pub fn set_disease(
+        &mut self,
+        species: SpeciesId,
+        disease: DiseaseParams,
+    ) -> Result<(), BioSimError> {
+        self.island.set_disease(species, disease)
+    }
+
+    // see migration::FoodGuided for a strategy that follows the food and
+    // migration::Dispersal for long-distance jumps
+    pub fn set_migration_strategy(
+        &mut self,
+        species: SpeciesId,
+        strategy: Arc<dyn MigrationStrategy>,
+    ) {
+        self.island.set_migration_strategy(species, strategy);
+    }
+
+    pub fn set_genetics(&mut self, genetics: Option<Genetics>) -> Result<(), BioSimError> {
+        self.island.set_genetics(genetics)
+    }
+
+    pub fn set_reproduction(&mut self, reproduction: Reproduction) {
+        self.island.set_reproduction(reproduction);
+    }
+
+    pub fn set_landscape_parameters(
+        &mut self,
+        cell_type: CellType,
+        f_max: f32,
+    ) -> Result<(), BioSimError> {
+        self.island.set_landscape_parameters(cell_type, f_max)
+    }
+
+    pub fn set_terrain(&mut self, terrain: Option<TerrainDynamics>) -> Result<(), BioSimError> {
+        self.island.set_terrain(terrain)
+    }
+
+    pub fn set_events(&mut self, events: Vec<Event>) -> Result<(), BioSimError> {
+        self.island.set_events(events)
+    }
+
+    pub fn set_climate(&mut self, climate: Vec<ClimateEvent>) -> Result<(), BioSimError> {
+        self.island.set_climate(climate)
+    }
+
+    pub fn set_regrowth(
+        &mut self,
+        cell_type: CellType,
+        regrowth: Regrowth,
+    ) -> Result<(), BioSimError> {
+        self.island.set_regrowth(cell_type, regrowth)
+    }
+
+    // write the population to csv files in dir from now on, starting with the current year
+    pub fn log_to_csv(&mut self, dir: &Path) -> Result<(), BioSimError> {
+        let mut log = CsvLog::create(dir, &self.island, self.offset)?;
+        log.write_year(self.island.year(), &self.island)?;
+        self.log = Some(log);
+
+        Ok(())
+    }
+
+    // run the simulation for num_years, continuing from the last simulated year
+    pub fn simulate(&mut self, num_years: u32) -> Result<(), BioSimError> {
+        for _ in 0..num_years {
+            self.island.yearly_cycle();
+
+            if let Some(log) = self.log.as_mut() {
+                log.write_year(self.island.year(), &self.island)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn year(&self) -> u32 {
+        self.island.year()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn island(&self) -> &Island {
+        &self.island
+    }
+
+    pub fn num_animals(&self) -> usize {
+        self.island.get_pop().iter().sum()
+    }
+
+    pub fn num_animals_per_species(&self) -> HashMap<String, usize> {
+        self.island
+            .species()
+            .iter()
+            .zip(self.island.get_pop())
+            .map(|((_, info), count)| (info.name.clone(), count))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+
+    const MAP: &str = "
+        WWWW
+        WLLW
+        WLLW
+        WWWW";
+
+    #[test]
+    fn test_initial_population() {
+        let ini_pop = vec![
+            ((1, 1), "Herbivore".to_string(), 50),
+            ((2, 2), "Carnivore".to_string(), 5),
+        ];
+        let sim = BioSim::new(MAP, ini_pop, 1).unwrap();
+
+        assert_eq!(sim.year(), 0);
+        assert_eq!(sim.num_animals(), 55);
+        assert_eq!(sim.num_animals_per_species()["Herbivore"], 50);
+        assert_eq!(sim.num_animals_per_species()["Carnivore"], 5);
+    }
+
+    #[test]
+    fn test_resume_simulation() {
+        let ini_pop = vec![((1, 1), "Herbivore".to_string(), 50)];
+        let mut sim = BioSim::new(MAP, ini_pop, 1).unwrap();
+
+        sim.simulate(5).unwrap();
+        assert_eq!(sim.year(), 5);
+
+        sim.simulate(10).unwrap();
+        assert_eq!(sim.year(), 15);
+    }
+
+    #[test]
+    fn test_same_seed_same_trajectory() {
+        let trajectory = |seed: u64| {
+            let ini_pop = vec![
+                ((1, 1), "Herbivore".to_string(), 50),
+                ((2, 2), "Carnivore".to_string(), 5),
+            ];
+            let mut sim = BioSim::new(MAP, ini_pop, seed).unwrap();
+
+            (0..20)
+                .map(|_| {
+                    sim.simulate(1).unwrap();
+                    sim.island().get_pop()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(trajectory(42), trajectory(42));
+    }
+
+    #[test]
+    fn test_padded_scenario() {
+        let mut config = Config::build(
+            "LLH".to_string(),
+            vec![((0, 0), "Herbivore".to_string(), 10)],
+        )
+        .unwrap();
+        assert!(matches!(
+            BioSim::from_config(&config),
+            Err(BioSimError::NonWaterBorder(_))
+        ));
+
+        config.pad_map = true;
+        let sim = BioSim::from_config(&config).unwrap();
+
+        assert_eq!(sim.island().width(), 5);
+        let herbs = sim
+            .island()
+            .cell((1, 1))
+            .unwrap()
+            .fauna
+            .as_ref()
+            .unwrap()
+            .count(SpeciesId::HERBIVORE);
+        assert_eq!(herbs, 10);
+    }
+
+    #[test]
+    fn test_add_population_mid_run() {
+        let ini_pop = vec![((1, 1), "Herbivore".to_string(), 50)];
+        let mut sim = BioSim::new(MAP, ini_pop, 1).unwrap();
+        sim.simulate(5).unwrap();
+
+        let carnivores = PopulationEntry::from(((2, 2), "Carnivore".to_string(), 5));
+        sim.add_population(&[carnivores]).unwrap();
+        assert_eq!(sim.num_animals_per_species()["Carnivore"], 5);
+
+        sim.simulate(5).unwrap();
+        assert_eq!(sim.year(), 10);
+    }
+
+    #[test]
+    fn test_csv_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_csv_{}", std::process::id()));
+        let ini_pop = vec![
+            ((1, 1), "Herbivore".to_string(), 50),
+            ((2, 2), "Carnivore".to_string(), 5),
+        ];
+        let mut sim = BioSim::new(MAP, ini_pop, 1).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(3).unwrap();
+
+        let totals = std::fs::read_to_string(dir.join(crate::output::TOTALS_FILE)).unwrap();
+        let lines: Vec<&str> = totals.lines().collect();
+        assert_eq!(lines[0], "year,Herbivore,Carnivore,Omnivore,ApexPredator");
+        assert_eq!(lines[1], "0,50,5,0,0");
+        assert_eq!(lines.len(), 5);
+
+        let cells = std::fs::read_to_string(dir.join(crate::output::CELLS_FILE)).unwrap();
+        let lines: Vec<&str> = cells.lines().collect();
+        assert_eq!(lines[0], "year,x,y,species,count");
+        assert_eq!(lines[1], "0,1,1,Herbivore,50");
+        assert_eq!(lines[2], "0,2,2,Carnivore,5");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_log_species_changed() {
+        let dir = std::env::temp_dir().join(format!("biosim_species_{}", std::process::id()));
+        let mut sim = BioSim::new(MAP, Vec::new(), 1).unwrap();
+        sim.log_to_csv(&dir).unwrap();
+
+        let fox = SpeciesEntry {
+            name: "Fox".to_string(),
+            display_name: None,
+            based_on: "Carnivore".to_string(),
+            params: HashMap::new(),
+            fodder: None,
+            prey: None,
+        };
+        sim.register_species(&fox).unwrap();
+        assert!(matches!(sim.simulate(1), Err(BioSimError::Scenario(_))));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_padded_csv_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_padded_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "LLH"
+            pad_map = true
+            ini_pop = [{ loc = [1, 0], pop = [{ species = "Herbivore", count = 10 }] }]
+
+            [terrain]
+            degrade_after = 5
+            recover_after = 5
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+
+        let cells = std::fs::read_to_string(dir.join(crate::output::CELLS_FILE)).unwrap();
+        assert_eq!(cells, "year,x,y,species,count\n0,1,0,Herbivore,10\n");
+        let terrain = std::fs::read_to_string(dir.join(crate::output::TERRAIN_FILE)).unwrap();
+        assert_eq!(terrain, "year,y,cells\n0,0,LLH\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_population_in_water() {
+        let ini_pop = vec![((0, 0), "Herbivore".to_string(), 10)];
+        let err = BioSim::new(MAP, ini_pop, 1).unwrap_err();
+        assert!(matches!(err, BioSimError::AnimalsInWater((0, 0))));
+    }
+
+    #[test]
+    fn test_population_outside_map() {
+        let ini_pop = vec![((7, 7), "Herbivore".to_string(), 10)];
+        let err = BioSim::new(MAP, ini_pop, 1).unwrap_err();
+        assert!(matches!(err, BioSimError::OutOfBounds((7, 7))));
+    }
+
+    #[test]
+    fn test_unknown_species() {
+        let ini_pop = vec![((1, 1), "Dragon".to_string(), 1)];
+        let err = BioSim::new(MAP, ini_pop, 1).unwrap_err();
+        assert!(matches!(err, BioSimError::UnknownSpecies(name) if name == "Dragon"));
+    }
+
+    #[test]
+    fn test_species_from_config() {
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 5
+            island_map = "WWWW\nWLLW\nWWWW"
+
+            [[species]]
+            name = "Fox"
+            display_name = "foxes"
+            based_on = "Carnivore"
+
+            [[species]]
+            name = "Wolf"
+            based_on = "Carnivore"
+            prey = ["Herbivore", "Fox"]
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [
+                { species = "Herbivore", count = 30 },
+                { species = "Fox", count = 4 },
+                { species = "Wolf", count = 2 },
+            ]
+
+            [animal_params.Wolf]
+            f = 80.0
+            "#,
+        )
+        .unwrap();
+
+        let mut sim = BioSim::from_config(&config).unwrap();
+        let wolf = sim.island().species().id("Wolf").unwrap();
+        assert_eq!(sim.island().species().params(wolf).f, 80.0);
+        assert_eq!(sim.num_animals_per_species()["Fox"], 4);
+        assert_eq!(sim.num_animals_per_species()["Wolf"], 2);
+        assert_eq!(sim.num_animals(), 36);
+
+        sim.simulate(5).unwrap();
+        assert_eq!(sim.island().get_pop().len(), 6);
+    }
+
+    #[test]
+    fn test_apex_predator_population() {
+        let ini_pop = vec![
+            ((1, 1), "Herbivore".to_string(), 50),
+            ((1, 1), "Carnivore".to_string(), 10),
+            ((2, 2), "ApexPredator".to_string(), 2),
+        ];
+        let mut sim = BioSim::new(MAP, ini_pop, 1).unwrap();
+
+        assert_eq!(sim.num_animals_per_species()["ApexPredator"], 2);
+        assert_eq!(sim.island().count(SpeciesId::APEX_PREDATOR), 2);
+
+        sim.simulate(3).unwrap();
+        assert_eq!(sim.year(), 3);
+    }
+
+    #[test]
+    fn test_trait_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_traits_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "WWWW\nWLLW\nWLLW\nWWWW"
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [
+                { species = "Herbivore", count = 50 },
+                { species = "Carnivore", count = 5 },
+            ]
+
+            [genetics.mutation]
+            mu = 0.05
+            delta_phi_max = 0.5
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(2).unwrap();
+
+        let traits = std::fs::read_to_string(dir.join(crate::output::TRAITS_FILE)).unwrap();
+        let lines: Vec<&str> = traits.lines().collect();
+        assert_eq!(lines[0], "year,species,trait,mean,variance");
+        assert!(
+            lines[1].starts_with("0,Herbivore,delta_phi_max,0,0"),
+            "{}",
+            lines[1]
+        );
+        assert!(lines.iter().any(|line| line.starts_with("2,Carnivore,mu,")));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_sexual_reproduction_needs_both_sexes() {
+        let scenario = |sex: &str| {
+            format!(
+                r#"
+            num_years = 0
+            reproduction = "sexual"
+            island_map = "WWWW\nWLLW\nWLLW\nWWWW"
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [{{ species = "Herbivore", age = 5, weight = 60.0, {sex}count = 50 }}]
+            "#
+            )
+        };
+
+        let config = Config::from_toml_str(&scenario("sex = \"Female\", ")).unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+        sim.simulate(1).unwrap();
+        assert!(sim.num_animals() <= 50);
+
+        let config = Config::from_toml_str(&scenario("")).unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+        sim.simulate(1).unwrap();
+        assert!(sim.num_animals() > 50);
+    }
+
+    #[test]
+    fn test_climate_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_climate_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "WWWW\nWLHW\nWWWW"
+            ini_pop = []
+
+            [[climate]]
+            name = "drought"
+            years = [2, 4]
+            cell_types = ["Lowland"]
+            f_max = 0.25
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(2).unwrap();
+        assert_eq!(sim.island().cell((1, 1)).unwrap().fodder, 200.0);
+        assert_eq!(sim.island().cell((2, 1)).unwrap().fodder, 300.0);
+        sim.simulate(3).unwrap();
+        assert_eq!(sim.island().cell((1, 1)).unwrap().fodder, 800.0);
+
+        let climate = std::fs::read_to_string(dir.join(crate::output::CLIMATE_FILE)).unwrap();
+        assert_eq!(climate, "year,event\n2,drought\n3,drought\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_disease_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_disease_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "WWWW\nWLLW\nWWWW"
+
+            [[ini_pop]]
+            loc = [1, 1]
+            pop = [
+                { species = "Herbivore", count = 40 },
+                { species = "Herbivore", count = 10, infection = "Infected" },
+            ]
+
+            [disease.Herbivore]
+            transmission = 0.1
+            recovery = 0.5
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(1).unwrap();
+
+        let totals = std::fs::read_to_string(dir.join(crate::output::DISEASE_FILE)).unwrap();
+        let lines: Vec<&str> = totals.lines().collect();
+        assert_eq!(lines[0], "year,species,susceptible,infected,recovered");
+        assert_eq!(lines[1], "0,Herbivore,40,10,0");
+        assert!(lines[2].starts_with("1,Herbivore,"), "{}", lines[2]);
+
+        let cells = std::fs::read_to_string(dir.join(crate::output::DISEASE_CELLS_FILE)).unwrap();
+        assert!(cells.contains("\n0,1,1,Herbivore,40,10,0\n"), "{cells}");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_event_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_events_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            pad_map = true
+            island_map = "LLL"
+            ini_pop = [{ loc = [0, 0], pop = [{ species = "Carnivore", count = 4 }] }]
+
+            [[events]]
+            kind = "fire"
+            year = 1
+            region = [[2, 0], [2, 0]]
+
+            [[events]]
+            kind = "cull"
+            year = 2
+            species = "Carnivore"
+            count = 10
+            region = [[0, 0], [0, 0]]
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(2).unwrap();
+
+        let events = std::fs::read_to_string(dir.join(crate::output::EVENTS_FILE)).unwrap();
+        let lines: Vec<&str> = events.lines().collect();
+        assert_eq!(lines[0], "year,event,cells,killed,moved");
+        // the regions refer to the map before padding, (2, 0) is land
+        assert_eq!(lines[1], "1,fire,1,0,0");
+        assert!(lines[2].starts_with("2,cull,"), "{}", lines[2]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_terrain_log() {
+        let dir = std::env::temp_dir().join(format!("biosim_terrain_{}", std::process::id()));
+        let config = Config::from_toml_str(
+            r#"
+            num_years = 0
+            island_map = "WWWW\nWLLW\nWWWW"
+            ini_pop = [{ loc = [1, 1], pop = [{ species = "Herbivore", weight = 30.0, count = 200 }] }]
+
+            [terrain]
+            degrade_after = 1
+            recover_after = 10
+            "#,
+        )
+        .unwrap();
+        let mut sim = BioSim::from_config(&config).unwrap();
+
+        sim.log_to_csv(&dir).unwrap();
+        sim.simulate(1).unwrap();
+
+        let terrain = std::fs::read_to_string(dir.join(crate::output::TERRAIN_FILE)).unwrap();
+        let lines: Vec<&str> = terrain.lines().collect();
+        assert_eq!(lines[0], "year,y,cells");
+        assert_eq!(lines[2], "0,1,WLLW");
+        assert_eq!(lines[5], "1,1,WHLW");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
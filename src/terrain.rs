@@ -0,0 +1,214 @@
+use crate::cell::CellType;
+use crate::error::BioSimError;
+use serde::Deserialize;
+
+// optional degradation and recovery of the land, in a scenario file:
+//
+// [terrain]
+// degrade_after = 3   years in a row with the fodder grazed down before a cell degrades
+// recover_after = 5   years in a row with low grazing before a cell recovers
+// exhausted = 0.05    fodder left at this share of f_max or less with grazers present
+//                     counts as grazed down
+// low_pressure = 0.2  grazers wanting this share of f_max or less count as low grazing
+//
+// The pressure is what the grazers want to eat, not what they found, so a bare cell
+// with hungry grazers is grazed down and never rests. Lowland degrades to Highland
+// and Highland to Desert. A cell recovers one step at a time, but never beyond the
+// type it has on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainDynamics {
+    pub degrade_after: u32,
+    pub recover_after: u32,
+    #[serde(default = "default_exhausted")]
+    pub exhausted: f32,
+    #[serde(default = "default_low_pressure")]
+    pub low_pressure: f32,
+}
+
+fn default_exhausted() -> f32 {
+    0.05
+}
+
+fn default_low_pressure() -> f32 {
+    0.2
+}
+
+// years in a row a cell has been grazed down or lightly grazed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TerrainCounter {
+    pub exhausted: u32,
+    pub rested: u32,
+}
+
+impl TerrainDynamics {
+    pub fn validate(&self) -> Result<(), BioSimError> {
+        for (key, years) in [
+            ("degrade_after", self.degrade_after),
+            ("recover_after", self.recover_after),
+        ] {
+            if years == 0 {
+                return Err(BioSimError::InvalidParameter {
+                    key: key.to_string(),
+                    value: 0.0,
+                    reason: "must be at least 1 year",
+                });
+            }
+        }
+        for (key, share) in [
+            ("exhausted", self.exhausted),
+            ("low_pressure", self.low_pressure),
+        ] {
+            if !(0.0..=1.0).contains(&share) {
+                return Err(BioSimError::InvalidParameter {
+                    key: key.to_string(),
+                    value: share,
+                    reason: "must be between 0 and 1",
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    // counts the year given the fodder the grazers wanted and the fodder left after
+    // they fed
+    pub fn record(&self, counter: &mut TerrainCounter, f_max: f32, demand: f32, after: f32) {
+        let exhausted = demand > 0.0 && after <= self.exhausted * f_max;
+        let rested = !exhausted && demand <= self.low_pressure * f_max;
+
+        counter.exhausted = if exhausted { counter.exhausted + 1 } else { 0 };
+        counter.rested = if rested { counter.rested + 1 } else { 0 };
+    }
+
+    // the type the cell turns into this year, if any
+    pub fn next_type(
+        &self,
+        current: CellType,
+        origin: CellType,
+        counter: &TerrainCounter,
+    ) -> Option<CellType> {
+        let degraded = match current {
+            CellType::Lowland => Some(CellType::Highland),
+            CellType::Highland => Some(CellType::Desert),
+            _ => None,
+        };
+        let recovered = match current {
+            CellType::Desert => Some(CellType::Highland),
+            CellType::Highland => Some(CellType::Lowland),
+            _ => None,
+        };
+
+        if counter.exhausted >= self.degrade_after {
+            return degraded;
+        }
+        if counter.rested >= self.recover_after && fertility(current) < fertility(origin) {
+            return recovered;
+        }
+        None
+    }
+}
+
+fn fertility(cell_type: CellType) -> u8 {
+    match cell_type {
+        CellType::Water => 0,
+        CellType::Desert => 1,
+        CellType::Highland => 2,
+        CellType::Lowland => 3,
+    }
+}
+
+#[cfg(test)]
+mod terrain_tests {
+    use super::*;
+
+    const DYNAMICS: TerrainDynamics = TerrainDynamics {
+        degrade_after: 2,
+        recover_after: 3,
+        exhausted: 0.05,
+        low_pressure: 0.2,
+    };
+
+    #[test]
+    fn test_record() {
+        let mut counter = TerrainCounter::default();
+
+        DYNAMICS.record(&mut counter, 800.0, 1000.0, 10.0);
+        DYNAMICS.record(&mut counter, 800.0, 1000.0, 0.0);
+        assert_eq!(
+            counter,
+            TerrainCounter {
+                exhausted: 2,
+                rested: 0
+            }
+        );
+
+        DYNAMICS.record(&mut counter, 800.0, 100.0, 700.0);
+        assert_eq!(
+            counter,
+            TerrainCounter {
+                exhausted: 0,
+                rested: 1
+            }
+        );
+
+        // grazers on a bare cell keep it grazed down, it never rests
+        DYNAMICS.record(&mut counter, 800.0, 50.0, 0.0);
+        DYNAMICS.record(&mut counter, 0.0, 50.0, 0.0);
+        assert_eq!(
+            counter,
+            TerrainCounter {
+                exhausted: 2,
+                rested: 0
+            }
+        );
+
+        // high demand that the fodder can still meet is neither
+        DYNAMICS.record(&mut counter, 800.0, 500.0, 300.0);
+        assert_eq!(counter, TerrainCounter::default());
+
+        // without grazers any cell rests, a Desert too
+        DYNAMICS.record(&mut counter, 800.0, 0.0, 0.0);
+        DYNAMICS.record(&mut counter, 0.0, 0.0, 0.0);
+        assert_eq!(
+            counter,
+            TerrainCounter {
+                exhausted: 0,
+                rested: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_next_type() {
+        let grazed = TerrainCounter {
+            exhausted: 2,
+            rested: 0,
+        };
+        let rested = TerrainCounter {
+            exhausted: 0,
+            rested: 3,
+        };
+        let lowland = CellType::Lowland;
+
+        assert_eq!(
+            DYNAMICS.next_type(lowland, lowland, &grazed),
+            Some(CellType::Highland)
+        );
+        assert_eq!(
+            DYNAMICS.next_type(CellType::Highland, lowland, &grazed),
+            Some(CellType::Desert)
+        );
+        assert_eq!(DYNAMICS.next_type(CellType::Desert, lowland, &grazed), None);
+
+        assert_eq!(
+            DYNAMICS.next_type(CellType::Desert, lowland, &rested),
+            Some(CellType::Highland)
+        );
+        assert_eq!(
+            DYNAMICS.next_type(CellType::Highland, CellType::Highland, &rested),
+            None
+        );
+        assert_eq!(DYNAMICS.next_type(lowland, lowland, &rested), None);
+    }
+}